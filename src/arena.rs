@@ -0,0 +1,223 @@
+//! Arenas with branded keys, which are compacted by tracing the entries reachable from roots.
+
+use crate::{nonmax::NonMaxU32, with_region, Region, Storable};
+
+/// A key of an entry in the `Arena<'c, _>`.
+///
+/// The position of the entry is stored as a `u32` with a niche, so an `Option<Key<'c>>` is no larger than a `u32`.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Key<'c>(NonMaxU32, Region<'c>);
+
+impl Key<'_>
+{
+  /// The position of the entry in insertion order.
+  #[inline]
+  pub fn index(self) -> usize
+  {
+    self.0.get()
+  }
+
+  /// The position of the entry as a `u32`, e.g. for serialization.
+  #[inline]
+  pub fn raw(self) -> u32
+  {
+    self.0.raw()
+  }
+}
+
+/// The trait of storable entries whose generative form holds keys of the arena they are stored in.
+pub trait Trace: Storable
+{
+  /// Invoke `f` with every key held by `value`.
+  fn trace<'c>(value: &Self::Generative<'c>, f: &mut dyn FnMut(Key<'c>));
+
+  /// Rebuild `value` for the compacted arena, replacing each key it holds with the key returned by `keys`.
+  ///
+  /// Every key which `trace` reports is live, so `keys.get` returns `Some` for it.
+  fn relink<'c, 'd>(value: Self::Generative<'c>, keys: &KeyMap<'c, 'd>) -> Self::Generative<'d>;
+}
+
+/// An arena of entries of kind `K`, whose keys are branded with lifetime `'c`.
+///
+/// Entries are never removed individually, so every `Key<'c>` refers to an entry and lookups need no checks. Instead,
+/// `compact` keeps only the entries reachable from a set of roots, and moves them into an arena with a new brand,
+/// together with a `KeyMap` which carries the keys that are still alive over:
+///
+/// ```
+/// # use genz::*;
+/// struct Node<'c> { value: u32, children: Vec<Key<'c>> }
+///
+/// impl Storable for Node<'static> {
+///   type Generative<'c> = Node<'c>;
+/// }
+///
+/// impl Trace for Node<'static> {
+///   fn trace<'c>(node: &Node<'c>, f: &mut dyn FnMut(Key<'c>)) {
+///     node.children.iter().copied().for_each(f)
+///   }
+///
+///   fn relink<'c, 'd>(node: Node<'c>, keys: &KeyMap<'c, 'd>) -> Node<'d> {
+///     Node { value: node.value, children: node.children.into_iter().map(|key| keys.get(key).unwrap()).collect() }
+///   }
+/// }
+///
+/// let sum = with_arena::<Node, _>(|mut arena| {
+///   let leaf = arena.insert(Node { value: 1, children: vec![] });
+///   let unreachable = arena.insert(Node { value: 100, children: vec![leaf] });
+///   let root = arena.insert(Node { value: 2, children: vec![leaf] });
+///
+///   arena.compact([root], |arena, keys| {
+///     assert_eq!((2, None), (arena.len(), keys.get(unreachable)));
+///
+///     let root = arena.get(keys.get(root).unwrap());
+///     root.value + root.children.iter().map(|&child| arena.get(child).value).sum::<u32>()
+///   })
+/// });
+///
+/// assert_eq!(3, sum);
+/// ```
+///
+/// Keys of the arena before compaction cannot be used with the compacted arena:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// struct Leaf(u32);
+/// # impl Storable for Leaf { type Generative<'c> = Leaf; }
+/// # impl Trace for Leaf {
+/// #   fn trace<'c>(_: &Leaf, _: &mut dyn FnMut(Key<'c>)) {}
+/// #   fn relink<'c, 'd>(leaf: Leaf, _: &KeyMap<'c, 'd>) -> Leaf { leaf }
+/// # }
+///
+/// with_arena::<Leaf, _>(|mut arena| {
+///   let key = arena.insert(Leaf(1));
+///   arena.compact([key], |arena, _| {
+///     arena.get(key); // fails because compaction brands the entries with a new lifetime
+///   });
+/// });
+/// ```
+pub struct Arena<'c, K: Storable>
+{
+  entries: Vec<K::Generative<'c>>,
+  region: Region<'c>
+}
+
+/// The keys of the entries of the `Arena<'c, _>` which were kept by compacting it into the `Arena<'d, _>`.
+pub struct KeyMap<'c, 'd>
+{
+  keys: Vec<Option<NonMaxU32>>,
+  _from: Region<'c>,
+  to: Region<'d>
+}
+
+/// Invoke `f` with an empty arena branded with an invariant lifetime.
+#[inline]
+pub fn with_arena<K: Storable, Z>(f: impl for <'c> FnOnce(Arena<'c, K>) -> Z) -> Z
+{
+  with_region(|region| f(Arena { entries: Vec::new(), region }))
+}
+
+impl<'c, K: Storable> Arena<'c, K>
+{
+  /// Store `value`, returning its key.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the arena already holds `u32::MAX` entries.
+  #[inline]
+  pub fn insert(&mut self, value: K::Generative<'c>) -> Key<'c>
+  {
+    let index = NonMaxU32::new(self.entries.len()).expect("an arena holds at most `u32::MAX` entries");
+    self.entries.push(value);
+    Key(index, self.region)
+  }
+
+  /// Returns a reference to the entry of `key`.
+  #[inline]
+  pub fn get(&self, key: Key<'c>) -> &K::Generative<'c>
+  {
+    // SAFETY: `key` was returned by `insert` on this arena, and entries are never removed while it has this brand.
+    unsafe { self.entries.get_unchecked(key.index()) }
+  }
+
+  /// Returns a mutable reference to the entry of `key`.
+  #[inline]
+  pub fn get_mut(&mut self, key: Key<'c>) -> &mut K::Generative<'c>
+  {
+    // SAFETY: `key` was returned by `insert` on this arena, and entries are never removed while it has this brand.
+    unsafe { self.entries.get_unchecked_mut(key.index()) }
+  }
+
+  /// Returns an iterator over the keys of every entry, in insertion order.
+  #[inline]
+  pub fn keys(&self) -> impl Iterator<Item = Key<'c>>
+  {
+    let region = self.region;
+    // SAFETY: `index < len`, and `insert` does not store more than `MAX_LEN` entries.
+    (0 .. self.entries.len()).map(move |index| Key(unsafe { NonMaxU32::new_unchecked(index) }, region))
+  }
+
+  /// The number of entries.
+  #[inline]
+  pub fn len(&self) -> usize
+  {
+    self.entries.len()
+  }
+
+  /// Returns `true` if the arena has no entries.
+  #[inline]
+  pub fn is_empty(&self) -> bool
+  {
+    self.entries.is_empty()
+  }
+}
+
+impl<'c, K: Trace> Arena<'c, K>
+{
+  /// Keep only the entries reachable from `roots`, invoking `f` with them in an arena branded with a new invariant
+  /// lifetime, and the keys they were moved to.
+  ///
+  /// The kept entries stay in insertion order and are rebuilt with `Trace::relink`, while the others are dropped.
+  pub fn compact<Z>(self, roots: impl IntoIterator<Item = Key<'c>>, f: impl for <'d> FnOnce(Arena<'d, K>, KeyMap<'c, 'd>) -> Z) -> Z
+  {
+    let mut live = vec![false; self.entries.len()];
+    let mut pending: Vec<Key<'c>> = Vec::new();
+    let mut mark = |key: Key<'c>, pending: &mut Vec<Key<'c>>| {
+      // SAFETY: `key` is of this arena, so it is less than the number of entries.
+      let live = unsafe { live.get_unchecked_mut(key.index()) };
+      if !*live {
+        *live = true;
+        pending.push(key);
+      }
+    };
+    roots.into_iter().for_each(|key| mark(key, &mut pending));
+    while let Some(key) = pending.pop() {
+      K::trace(self.get(key), &mut |key| mark(key, &mut pending));
+    }
+
+    let mut kept = 0;
+    let keys = live.iter().map(|&live| live.then(|| {
+      // SAFETY: fewer entries than there are in the arena were kept so far, which is at most `MAX_LEN`.
+      let index = unsafe { NonMaxU32::new_unchecked(kept) };
+      kept += 1;
+      index
+    })).collect();
+
+    with_region(|to| {
+      let keys = KeyMap { keys, _from: self.region, to };
+      let entries = self.entries.into_iter().zip(live).filter_map(|(entry, live)| live.then(|| K::relink(entry, &keys)));
+      let entries = entries.collect();
+      f(Arena { entries, region: to }, keys)
+    })
+  }
+}
+
+impl<'c, 'd> KeyMap<'c, 'd>
+{
+  /// Returns the key of the entry of `key` in the compacted arena, or `None` if the entry was not reachable.
+  #[inline]
+  pub fn get(&self, key: Key<'c>) -> Option<Key<'d>>
+  {
+    // SAFETY: `key` is of the arena before compaction, which had an entry in `keys` per key.
+    unsafe { *self.keys.get_unchecked(key.index()) }.map(|index| Key(index, self.to))
+  }
+}
@@ -0,0 +1,186 @@
+//! Heterogeneous node storage with branded typed ids.
+
+use std::{any::{Any, TypeId}, collections::HashMap, marker::PhantomData, mem::ManuallyDrop};
+use crate::{nonmax::NonMaxU32, Region, Storable, UniqueType};
+
+/// A reference to a node of kind `K` stored in the `AstArena<'c>`.
+///
+/// The position of the node is stored as a `u32` with a niche, so an `Option<NodeId<'c, K>>` is no larger than a
+/// `NodeId<'c, K>`.
+pub struct NodeId<'c, K>(NonMaxU32, Region<'c>, PhantomData<fn() -> K>);
+
+impl<K> Clone for NodeId<'_, K>
+{
+  #[inline]
+  fn clone(&self) -> Self
+  {
+    *self
+  }
+}
+
+impl<K> Copy for NodeId<'_, K> {}
+
+impl<K> PartialEq for NodeId<'_, K>
+{
+  #[inline]
+  fn eq(&self, other: &Self) -> bool
+  {
+    self.0 == other.0
+  }
+}
+
+impl<K> Eq for NodeId<'_, K> {}
+
+impl<'c, K> NodeId<'c, K>
+{
+  /// The position of the node among the nodes of kind `K`.
+  #[inline]
+  pub fn index(self) -> usize
+  {
+    self.0.get()
+  }
+
+  /// The position of the node as a `u32`, e.g. for serialization.
+  #[inline]
+  pub fn raw(self) -> u32
+  {
+    self.0.raw()
+  }
+
+  /// The region of the arena the node belongs to.
+  #[inline]
+  pub fn brand(self) -> Region<'c>
+  {
+    self.1
+  }
+}
+
+/// Evidence that node kind `K` is registered with the `AstArena<'c>`.
+pub struct Kind<'c, K>(PhantomData<(Region<'c>, fn() -> K)>);
+
+impl<K> Clone for Kind<'_, K>
+{
+  #[inline]
+  fn clone(&self) -> Self
+  {
+    *self
+  }
+}
+
+impl<K> Copy for Kind<'_, K> {}
+
+/// Storage for nodes of several kinds, referenced by ids branded with lifetime `'c`.
+///
+/// A node kind is a `Storable` type, whose generative form may contain `NodeId<'c, _>` references to other nodes.
+/// Because creating the arena and registering each kind consumes the corresponding `UniqueType<'c, _>` marker, there is
+/// exactly one arena and one store per kind for lifetime `'c`, so every `NodeId<'c, K>` can be dereferenced without
+/// validation:
+///
+/// ```
+/// # use genz::*;
+/// enum Expr<'c> {
+///   Lit(i64),
+///   Add(NodeId<'c, Expr<'static>>, NodeId<'c, Expr<'static>>)
+/// }
+///
+/// impl Storable for Expr<'static> {
+///   type Generative<'c> = Expr<'c>;
+/// }
+///
+/// fn eval<'c>(arena: &AstArena<'c>, id: NodeId<'c, Expr<'static>>) -> i64
+/// {
+///   match arena.get(id) {
+///     Expr::Lit(n) => *n,
+///     Expr::Add(a, b) => eval(arena, *a) + eval(arena, *b)
+///   }
+/// }
+///
+/// let result = with_types::<(AstArena, Expr), _>(|_, (arena, expr)| {
+///   let mut arena = AstArena::new(arena);
+///   let expr = arena.register(expr);
+///
+///   let one = arena.alloc(expr, Expr::Lit(1));
+///   let two = arena.alloc(expr, Expr::Lit(2));
+///   let sum = arena.alloc(expr, Expr::Add(one, two));
+///   eval(&arena, sum)
+/// });
+///
+/// assert_eq!(3, result);
+/// ```
+pub struct AstArena<'c>
+{
+  stores: HashMap<TypeId, Box<dyn Any>>,
+  region: Region<'c>
+}
+
+impl<'c> AstArena<'c>
+{
+  /// Create an empty arena.
+  #[inline]
+  pub fn new(marker: UniqueType<'c, AstArena<'static>>) -> Self
+  {
+    AstArena { stores: HashMap::new(), region: marker.into() }
+  }
+
+  /// Register node kind `K`, returning evidence that nodes of that kind may be allocated.
+  pub fn register<K: Storable + 'static>(&mut self, _: UniqueType<'c, K>) -> Kind<'c, K>
+  {
+    self.stores.insert(TypeId::of::<K>(), Box::new(Vec::<K>::new()));
+    Kind(PhantomData)
+  }
+
+  /// Store `node`, returning its id.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the arena already holds `u32::MAX` nodes of kind `K`.
+  pub fn alloc<K: Storable + 'static>(&mut self, _: Kind<'c, K>, node: K::Generative<'c>) -> NodeId<'c, K>
+  {
+    let node = ManuallyDrop::new(node);
+    // SAFETY: `K::Generative<'c>` and `K::Generative<'static>` differ only in lifetimes, so they have the same layout,
+    // and `node` is not dropped.
+    let node: K::Generative<'static> = unsafe { std::ptr::read((&*node as *const K::Generative<'c>).cast()) };
+
+    let region = self.region;
+    let store = self.store_mut::<K>();
+    let index = NonMaxU32::new(store.len()).expect("an arena holds at most `u32::MAX` nodes of each kind");
+    store.push(node.into());
+    NodeId(index, region, PhantomData)
+  }
+
+  /// Returns a reference to the node with the given `id`.
+  #[inline]
+  pub fn get<K: Storable + 'static>(&self, id: NodeId<'c, K>) -> &K::Generative<'c>
+  {
+    let store = self.stores[&TypeId::of::<K>()].downcast_ref::<Vec<K>>().expect("stores are keyed by their kind");
+    // SAFETY: `id` was returned by `alloc` on this arena, and nodes are never removed.
+    let node: &K::Generative<'static> = unsafe { store.get_unchecked(id.index()) }.borrow();
+    // SAFETY: the node was stored with lifetime `'c`.
+    unsafe { &*(node as *const K::Generative<'static>).cast::<K::Generative<'c>>() }
+  }
+
+  /// Returns a mutable reference to the node with the given `id`.
+  #[inline]
+  pub fn get_mut<K: Storable + 'static>(&mut self, id: NodeId<'c, K>) -> &mut K::Generative<'c>
+  {
+    // SAFETY: `id` was returned by `alloc` on this arena, and nodes are never removed.
+    let node: &mut K::Generative<'static> = unsafe { self.store_mut::<K>().get_unchecked_mut(id.index()) }.borrow_mut();
+    // SAFETY: the node was stored with lifetime `'c`.
+    unsafe { &mut *(node as *mut K::Generative<'static>).cast::<K::Generative<'c>>() }
+  }
+
+  /// The number of nodes of kind `K`.
+  #[inline]
+  pub fn len<K: Storable + 'static>(&self, _: Kind<'c, K>) -> usize
+  {
+    self.stores[&TypeId::of::<K>()].downcast_ref::<Vec<K>>().expect("stores are keyed by their kind").len()
+  }
+
+  #[inline]
+  fn store_mut<K: Storable + 'static>(&mut self) -> &mut Vec<K>
+  {
+    self.stores.get_mut(&TypeId::of::<K>())
+      .and_then(|store| store.downcast_mut())
+      .expect("kinds are registered before nodes are allocated")
+  }
+}
@@ -0,0 +1,171 @@
+//! Dense two-way mapping between branded ids and values.
+
+use std::{collections::{hash_map::Entry, HashMap}, hash::Hash};
+use crate::{nonmax::NonMaxU32, with_region, Brand, DynRegion, Region};
+
+/// An id of a value stored in the `BiMap<B, _>`.
+///
+/// The position of the value is stored as a `u32` with a niche, so an `Option<BiMapId<B>>` is no larger than a
+/// `BiMapId<B>`.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct BiMapId<B>(NonMaxU32, B);
+
+impl<B: Brand> BiMapId<B>
+{
+  /// The position of the value in insertion order.
+  #[inline]
+  pub fn index(self) -> usize
+  {
+    self.0.get()
+  }
+
+  /// The position of the value as a `u32`, e.g. for serialization.
+  #[inline]
+  pub fn raw(self) -> u32
+  {
+    self.0.raw()
+  }
+
+  /// The brand of the structure the id belongs to.
+  #[inline]
+  pub fn brand(self) -> B
+  {
+    self.1
+  }
+}
+
+/// A mapping from densely allocated ids with brand `B` to values, and from values back to ids.
+///
+/// Values are hashed once when they are inserted. Retrieving the value of an id is infallible and does not hash:
+///
+/// ```
+/// # use genz::*;
+/// with_bimap(|mut symbols| {
+///   let x = symbols.insert("x");
+///   let y = symbols.insert("y");
+///
+///   assert_eq!(x, symbols.insert("x"));
+///   assert_eq!("y", *symbols.get(y));
+///   assert_eq!(Some(x), symbols.id_of(&"x"));
+///   assert_eq!(None, symbols.id_of(&"z"));
+/// });
+/// ```
+///
+/// Ids of one map cannot be used with another:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// with_bimap(|mut a| {
+///   with_bimap(|b: BiMap<_, &str>| {
+///     b.get(a.insert("x")); // fails because `a` and `b` have different lifetimes
+///   });
+/// });
+/// ```
+///
+/// unless the maps are branded with a `DynRegion`, in which case using an id of another map panics:
+///
+/// ```should_panic
+/// # use genz::*;
+/// let mut a = BiMap::new();
+/// let b = BiMap::<_, &str>::new();
+/// b.get(a.insert("x")); // panics because the id belongs to `a`
+/// ```
+pub struct BiMap<B, V>
+{
+  brand: B,
+  values: Vec<V>,
+  ids: HashMap<V, BiMapId<B>>
+}
+
+/// Invoke `f` with an empty map branded with an invariant lifetime.
+#[inline]
+pub fn with_bimap<V, Z>(f: impl for <'c> FnOnce(BiMap<Region<'c>, V>) -> Z) -> Z
+{
+  with_region(|region| f(BiMap { brand: region, values: Vec::new(), ids: HashMap::new() }))
+}
+
+impl<V> BiMap<DynRegion, V>
+{
+  /// Create an empty map branded with a new `DynRegion`.
+  #[inline]
+  #[allow(clippy::new_without_default)]
+  pub fn new() -> Self
+  {
+    BiMap { brand: DynRegion::new(), values: Vec::new(), ids: HashMap::new() }
+  }
+}
+
+impl<B: Brand, V: Hash + Eq + Clone> BiMap<B, V>
+{
+  /// Returns the id of `value`, inserting it if it is not already stored.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `value` is not stored and the map already holds `u32::MAX` values.
+  pub fn insert(&mut self, value: V) -> BiMapId<B>
+  {
+    match self.ids.entry(value) {
+      Entry::Occupied(entry) => *entry.get(),
+      Entry::Vacant(entry) => {
+        let id = BiMapId(NonMaxU32::new(self.values.len()).expect("a map holds at most `u32::MAX` values"), self.brand);
+        self.values.push(entry.key().clone());
+        *entry.insert(id)
+      }
+    }
+  }
+
+  /// Returns the id of `value`, or `None` if it is not stored.
+  #[inline]
+  pub fn id_of(&self, value: &V) -> Option<BiMapId<B>>
+  {
+    self.ids.get(value).copied()
+  }
+}
+
+impl<B: Brand, V> BiMap<B, V>
+{
+  /// The brand of the map.
+  #[inline]
+  pub fn brand(&self) -> B
+  {
+    self.brand
+  }
+
+  /// Returns the value with the given `id`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `id` is of another map, which can only happen for brands checked at runtime.
+  #[inline]
+  pub fn get(&self, id: BiMapId<B>) -> &V
+  {
+    assert!(self.brand.same_brand(id.1), "the id is of another map");
+    // SAFETY: `id` was returned by `insert` on this map, and values are never removed.
+    unsafe { self.values.get_unchecked(id.index()) }
+  }
+
+  /// The number of stored values.
+  #[inline]
+  pub fn len(&self) -> usize
+  {
+    self.values.len()
+  }
+
+  /// Returns `true` if no values are stored.
+  #[inline]
+  pub fn is_empty(&self) -> bool
+  {
+    self.values.is_empty()
+  }
+
+  /// Iterate over the ids and values in insertion order.
+  #[inline]
+  pub fn iter(&self) -> impl Iterator<Item = (BiMapId<B>, &V)>
+  {
+    let brand = self.brand;
+    // SAFETY: `index < len <= MAX_LEN`, because `insert` does not store more values.
+    self.values.iter().enumerate().map(move |(index, value)| {
+      (BiMapId(unsafe { NonMaxU32::new_unchecked(index) }, brand), value)
+    })
+  }
+}
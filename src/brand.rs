@@ -0,0 +1,67 @@
+//! Abstraction over lifetime and runtime brands.
+
+use crate::{DynRegion, Region};
+
+/// A token proving that values share an origin.
+///
+/// Implemented by `Region<'c>`, for which a common origin is proven by the type checker, and by `DynRegion`, for which
+/// it is checked at runtime. Data structures written against `Brand` can run in either mode, as do `Csr`, `Fenwick`,
+/// `BiMap`, `Pool`, `Source`, `ByteCursor`, `MutationGuard` and `MapGuard`, which are branded with a `Region` by their
+/// `with_*` functions and with a `DynRegion` by their `new` constructors:
+///
+/// ```
+/// # use genz::*;
+/// struct Slot<B: Brand> {
+///   brand: B,
+///   index: usize
+/// }
+///
+/// struct Slots<B: Brand> {
+///   brand: B,
+///   values: Vec<u32>
+/// }
+///
+/// impl<B: Brand> Slots<B> {
+///   fn get(&self, slot: Slot<B>) -> Option<u32> {
+///     self.brand.same_brand(slot.brand).then(|| self.values[slot.index])
+///   }
+/// }
+///
+/// with_region(|region| {
+///   let slots = Slots { brand: region, values: vec![1] };
+///   assert_eq!(Some(1), slots.get(Slot { brand: region, index: 0 }));
+/// });
+///
+/// let slots = Slots { brand: DynRegion::new(), values: vec![1] };
+/// assert_eq!(None, slots.get(Slot { brand: DynRegion::new(), index: 0 }));
+/// ```
+pub trait Brand: Copy
+{
+  /// `true` if `same_brand` is decided at runtime, and `false` if it is proven at compile time.
+  const CHECKED: bool;
+
+  /// Returns `true` if `self` and `other` are the same brand.
+  fn same_brand(self, other: Self) -> bool;
+}
+
+impl Brand for Region<'_>
+{
+  const CHECKED: bool = false;
+
+  #[inline(always)]
+  fn same_brand(self, _: Self) -> bool
+  {
+    true
+  }
+}
+
+impl Brand for DynRegion
+{
+  const CHECKED: bool = true;
+
+  #[inline]
+  fn same_brand(self, other: Self) -> bool
+  {
+    self == other
+  }
+}
@@ -0,0 +1,143 @@
+//! Builders whose fields are checked at compile time.
+
+use std::{any::{Any, TypeId}, collections::HashMap, marker::PhantomData};
+use crate::{Region, UniqueType};
+
+mod sealed
+{
+  use super::{Here, There};
+
+  pub trait Sealed<F, I> {}
+
+  impl<F, S> Sealed<F, Here> for (F, S) {}
+
+  impl<F, G, S: Sealed<F, I>, I> Sealed<F, There<I>> for (G, S) {}
+}
+
+/// The index of a field at the head of a type-level set.
+pub struct Here;
+
+/// The index of a field in the tail of a type-level set.
+pub struct There<I>(PhantomData<I>);
+
+/// Evidence that the type-level set `Self` contains the field `F`, at index `I`.
+///
+/// The index is inferred, so bounds are written with a free parameter, e.g. `S: Has<Host, I>`. The trait is sealed,
+/// so only sets built by `Builder::set` and `Injector::register` contain fields:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// struct Host;
+/// struct Port;
+///
+/// impl Has<Port, Here> for (Host, ()) {} // fails because `Has` is sealed
+/// ```
+pub trait Has<F, I>: sealed::Sealed<F, I> {}
+
+impl<F, S> Has<F, Here> for (F, S) {}
+
+impl<F, G, S: Has<F, I>, I> Has<F, There<I>> for (G, S) {}
+
+/// A value which may be built from the set of fields `S`, found at the indices `I`.
+pub trait Build<S, I>: Sized
+{
+  /// Build the value, taking each field from `fields`.
+  fn build(fields: Fields<S>) -> Self;
+}
+
+/// The fields set on a builder, as the type-level set `S`.
+pub struct Fields<S>
+{
+  values: HashMap<TypeId, Box<dyn Any>>,
+  _set: PhantomData<fn() -> S>
+}
+
+impl<S> Fields<S>
+{
+  /// Take the field `F`.
+  ///
+  /// Panics if the field has already been taken.
+  #[inline]
+  pub fn take<F: 'static, I>(&mut self) -> F
+    where
+      S: Has<F, I>
+  {
+    let value = self.values.remove(&TypeId::of::<F>()).expect("each field is taken once");
+    *value.downcast().expect("fields are stored by their type id")
+  }
+}
+
+/// A builder in the region `'c`, whose fields are the type-level set `S`.
+///
+/// Setting a field consumes its marker, so each field is set at most once, and building requires the set to contain
+/// every field the built value needs:
+///
+/// ```
+/// # use genz::*;
+/// struct Host(&'static str);
+/// struct Port(u16);
+///
+/// struct Server { host: Host, port: Port }
+///
+/// impl<S, I0, I1> Build<S, (I0, I1)> for Server
+///   where
+///     S: Has<Host, I0> + Has<Port, I1>
+/// {
+///   fn build(mut fields: Fields<S>) -> Self
+///   {
+///     Server { host: fields.take::<Host, I0>(), port: fields.take::<Port, I1>() }
+///   }
+/// }
+///
+/// let server: Server = with_types::<(Host, Port), _>(|region, (host, port)| {
+///   Builder::new(region).set(port, Port(80)).set(host, Host("localhost")).build()
+/// });
+///
+/// assert_eq!(("localhost", 80), (server.host.0, server.port.0));
+/// ```
+///
+/// ```compile_fail
+/// # use genz::*;
+/// # struct Host(&'static str);
+/// # struct Port(u16);
+/// # struct Server { host: Host, port: Port }
+/// # impl<S, I0, I1> Build<S, (I0, I1)> for Server where S: Has<Host, I0> + Has<Port, I1> {
+/// #   fn build(mut fields: Fields<S>) -> Self { Server { host: fields.take::<Host, I0>(), port: fields.take::<Port, I1>() } }
+/// # }
+/// let server: Server = with_types::<(Host, Port), _>(|region, (host, _)| {
+///   Builder::new(region).set(host, Host("localhost")).build() // fails because `Port` is never set
+/// });
+/// ```
+pub struct Builder<'c, S = ()>
+{
+  values: HashMap<TypeId, Box<dyn Any>>,
+  _region: PhantomData<(Region<'c>, fn() -> S)>
+}
+
+impl<'c> Builder<'c>
+{
+  /// Create a builder with no fields set.
+  #[inline]
+  pub fn new(_: Region<'c>) -> Self
+  {
+    Builder { values: HashMap::new(), _region: PhantomData }
+  }
+}
+
+impl<'c, S> Builder<'c, S>
+{
+  /// Set the field `F`, consuming its marker.
+  #[inline]
+  pub fn set<F: 'static>(mut self, _: UniqueType<'c, F>, value: F) -> Builder<'c, (F, S)>
+  {
+    self.values.insert(TypeId::of::<F>(), Box::new(value));
+    Builder { values: self.values, _region: PhantomData }
+  }
+
+  /// Build a value from the fields.
+  #[inline]
+  pub fn build<T: Build<S, I>, I>(self) -> T
+  {
+    T::build(Fields { values: self.values, _set: PhantomData })
+  }
+}
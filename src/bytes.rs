@@ -0,0 +1,182 @@
+//! Binary parsing with branded offsets.
+
+use std::ops::Range;
+use crate::{with_region, Brand, DynRegion, Region};
+
+/// A range of the buffer of the `ByteCursor<'_, B>` which is known to be in-bounds.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ByteRange<B>
+{
+  start: usize,
+  end: usize,
+  brand: B
+}
+
+impl<B: Brand> ByteRange<B>
+{
+  /// The brand of the cursor the range belongs to.
+  #[inline]
+  pub fn brand(self) -> B
+  {
+    self.brand
+  }
+
+  /// The range of offsets covered.
+  #[inline]
+  pub fn range(self) -> Range<usize>
+  {
+    self.start .. self.end
+  }
+
+  /// The length of the range in bytes.
+  #[inline]
+  pub fn len(self) -> usize
+  {
+    self.end - self.start
+  }
+
+  /// Returns `true` if the range is empty.
+  #[inline]
+  pub fn is_empty(self) -> bool
+  {
+    self.start == self.end
+  }
+}
+
+/// A cursor over a byte buffer whose parsed fields carry the brand `B`.
+///
+/// Taking a field checks that it is in-bounds once, after which it can be re-read without any further checks:
+///
+/// ```
+/// # use genz::*;
+/// // a length-prefixed name followed by a little-endian id
+/// let (name, id) = with_bytes(&[3, b'a', b'b', b'c', 7, 0], |mut cursor| {
+///   let len = cursor.u8()?;
+///   let name = cursor.take(len as usize)?;
+///   let id = cursor.u16_le()?;
+///   Some((cursor.slice(name).to_vec(), id))
+/// }).unwrap();
+///
+/// assert_eq!(b"abc".to_vec(), name);
+/// assert_eq!(7, id);
+///
+/// // `None` is returned because the field runs past the end of the buffer
+/// assert!(with_bytes(&[4, b'a'], |mut cursor| { let len = cursor.u8()?; cursor.take(len as usize).map(|_| ()) }).is_none());
+/// ```
+///
+/// Ranges of one buffer cannot be read from another:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// with_bytes(&[0], |mut a| {
+///   with_bytes(&[], |b| {
+///     b.slice(a.take(1).unwrap()); // fails because `a` and `b` have different lifetimes
+///   });
+/// });
+/// ```
+///
+/// A cursor created by `ByteCursor::new` is branded with a `DynRegion` instead, and reading a range of another cursor
+/// panics:
+///
+/// ```should_panic
+/// # use genz::*;
+/// let (mut a, b) = (ByteCursor::new(&[0]), ByteCursor::new(&[0]));
+/// b.slice(a.take(1).unwrap()); // panics because the range belongs to `a`
+/// ```
+pub struct ByteCursor<'b, B>
+{
+  bytes: &'b [u8],
+  position: usize,
+  brand: B
+}
+
+/// Invoke `f` with a cursor at the start of `bytes`, branded with an invariant lifetime.
+#[inline]
+pub fn with_bytes<'b, Z>(bytes: &'b [u8], f: impl for <'c> FnOnce(ByteCursor<'b, Region<'c>>) -> Z) -> Z
+{
+  with_region(|region| f(ByteCursor { bytes, position: 0, brand: region }))
+}
+
+impl<'b> ByteCursor<'b, DynRegion>
+{
+  /// Create a cursor at the start of `bytes`, branded with a new `DynRegion`.
+  #[inline]
+  pub fn new(bytes: &'b [u8]) -> Self
+  {
+    ByteCursor { bytes, position: 0, brand: DynRegion::new() }
+  }
+}
+
+macro_rules! read_int {
+  ($name:ident, $ty:ty, $from:ident) => {
+    #[doc = concat!("Read a `", stringify!($ty), "` using `", stringify!($ty), "::", stringify!($from), "`, or return `None` if there are too few bytes left.")]
+    #[inline]
+    pub fn $name(&mut self) -> Option<$ty>
+    {
+      self.take_array().map(<$ty>::$from)
+    }
+  };
+}
+
+impl<'b, B: Brand> ByteCursor<'b, B>
+{
+  /// The brand of the cursor.
+  #[inline]
+  pub fn brand(&self) -> B
+  {
+    self.brand
+  }
+
+  /// The offset of the next unread byte.
+  #[inline]
+  pub fn position(&self) -> usize
+  {
+    self.position
+  }
+
+  /// The number of unread bytes.
+  #[inline]
+  pub fn remaining(&self) -> usize
+  {
+    self.bytes.len() - self.position
+  }
+
+  /// Advance past the next `len` bytes, returning their range, or return `None` if there are too few bytes left.
+  #[inline]
+  pub fn take(&mut self, len: usize) -> Option<ByteRange<B>>
+  {
+    (len <= self.remaining()).then(|| {
+      let start = self.position;
+      self.position += len;
+      ByteRange { start, end: self.position, brand: self.brand }
+    })
+  }
+
+  /// Read the next `N` bytes, or return `None` if there are too few bytes left.
+  #[inline]
+  pub fn take_array<const N: usize>(&mut self) -> Option<[u8; N]>
+  {
+    self.take(N).map(|range| self.slice(range).try_into().expect("the range has length `N`"))
+  }
+
+  read_int!(u8, u8, from_le_bytes);
+  read_int!(u16_le, u16, from_le_bytes);
+  read_int!(u16_be, u16, from_be_bytes);
+  read_int!(u32_le, u32, from_le_bytes);
+  read_int!(u32_be, u32, from_be_bytes);
+  read_int!(u64_le, u64, from_le_bytes);
+  read_int!(u64_be, u64, from_be_bytes);
+
+  /// Returns the bytes covered by `range`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `range` is of another cursor, which can only happen for brands checked at runtime.
+  #[inline]
+  pub fn slice(&self, range: ByteRange<B>) -> &'b [u8]
+  {
+    assert!(self.brand.same_brand(range.brand), "the range is of another cursor");
+    // SAFETY: `range` was checked against this buffer by `take`.
+    unsafe { self.bytes.get_unchecked(range.start .. range.end) }
+  }
+}
@@ -0,0 +1,95 @@
+//! Caches which cannot outlive a region.
+
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+use crate::Region;
+
+/// A cache whose entries live no longer than the region `'c`.
+///
+/// Because the cache is branded with `'c`, it cannot escape the closure which received the region, so its entries are
+/// dropped by the time the region ends and cannot leak into the next one. Values may themselves carry the brand:
+///
+/// ```
+/// # use genz::*;
+/// for request in 0 .. 2 {
+///   with_region(|region| {
+///     let mut cache = RegionCache::<u32, (Region, u32)>::new(region);
+///     assert!(cache.is_empty());
+///
+///     let (_, doubled) = *cache.get_or_insert_with(request, || (region, request * 2));
+///     assert_eq!(request * 2, doubled);
+///   });
+/// }
+/// ```
+///
+/// ```compile_fail
+/// # use genz::*;
+/// let cache = with_region(|region| RegionCache::<u32, u32>::new(region)); // fails because the cache escapes
+/// ```
+pub struct RegionCache<'c, K, V>
+{
+  entries: HashMap<K, V>,
+  _region: Region<'c>
+}
+
+impl<'c, K: Hash + Eq, V> RegionCache<'c, K, V>
+{
+  /// Create an empty cache for `region`.
+  #[inline]
+  pub fn new(region: Region<'c>) -> Self
+  {
+    RegionCache { entries: HashMap::new(), _region: region }
+  }
+
+  /// Returns the cached value for `key`, if any.
+  #[inline]
+  pub fn get<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+      K: Borrow<Q>
+  {
+    self.entries.get(key)
+  }
+
+  /// Returns the cached value for `key`, computing and caching it with `f` if it is missing.
+  #[inline]
+  pub fn get_or_insert_with(&mut self, key: K, f: impl FnOnce() -> V) -> &mut V
+  {
+    self.entries.entry(key).or_insert_with(f)
+  }
+
+  /// Cache `value` for `key`, returning the previously cached value, if any.
+  #[inline]
+  pub fn insert(&mut self, key: K, value: V) -> Option<V>
+  {
+    self.entries.insert(key, value)
+  }
+
+  /// Remove and return the cached value for `key`, if any.
+  #[inline]
+  pub fn remove<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<V>
+    where
+      K: Borrow<Q>
+  {
+    self.entries.remove(key)
+  }
+
+  /// Remove every entry.
+  #[inline]
+  pub fn clear(&mut self)
+  {
+    self.entries.clear()
+  }
+
+  /// The number of cached entries.
+  #[inline]
+  pub fn len(&self) -> usize
+  {
+    self.entries.len()
+  }
+
+  /// Returns `true` if nothing is cached.
+  #[inline]
+  pub fn is_empty(&self) -> bool
+  {
+    self.entries.is_empty()
+  }
+}
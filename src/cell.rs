@@ -0,0 +1,271 @@
+//! Cells whose contents are borrowed through a token which is unique to its region.
+
+use std::{cell::UnsafeCell, fmt};
+use crate::{with_region, Region};
+
+/// The token of region `'c`, which grants access to the contents of every `TokenCell<'c, _>`.
+///
+/// Only `with_token` creates tokens, each with a fresh lifetime, so there is never more than one token per region. A
+/// shared borrow of the token therefore excludes every mutable borrow of the cells, and a mutable borrow of it
+/// excludes every other borrow:
+///
+/// ```
+/// # use genz::*;
+/// with_token(|mut token| {
+///   let region = token.region();
+///   let (a, b) = (TokenCell::new(1, region), TokenCell::new(2, region));
+///   let cells = [&a, &b, &a];
+///
+///   // the cells are shared, yet can be mutated one at a time
+///   for cell in cells {
+///     *cell.borrow_mut(&mut token) *= 10;
+///   }
+///   assert_eq!((100, 20), (*a.borrow(&token), *b.borrow(&token)));
+/// });
+/// ```
+///
+/// A token cannot open the cells of another region:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// with_token(|a| with_token(|b| {
+///   let cell = TokenCell::new(1, a.region());
+///   cell.borrow(&b); // fails because the lifetimes differ
+/// }));
+/// ```
+///
+/// Nor hand out a reference while the contents are mutably borrowed:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// with_token(|mut token| {
+///   let cell = TokenCell::new(1, token.region());
+///   let value = cell.borrow_mut(&mut token);
+///   let other = cell.borrow(&token); // fails because the token is mutably borrowed
+///   *value += *other;
+/// });
+/// ```
+pub struct Token<'c>(Region<'c>);
+
+/// A value which is borrowed through the `Token<'c>` of its region rather than through the cell itself.
+///
+/// Any number of shared references to a cell may exist, and the token decides which of them may access the contents.
+#[repr(transparent)]
+pub struct TokenCell<'c, T: ?Sized>
+{
+  region: Region<'c>,
+  value: UnsafeCell<T>
+}
+
+// SAFETY: sharing the cell between threads shares its contents through the token, which requires `T: Sync` for shared
+// borrows of the token and `T: Send` for mutable ones, since those may move the contents out.
+unsafe impl<T: ?Sized + Send + Sync> Sync for TokenCell<'_, T> {}
+
+/// Invoke `f` with the token of a region with an invariant lifetime.
+#[inline]
+pub fn with_token<Z>(f: impl for <'c> FnOnce(Token<'c>) -> Z) -> Z
+{
+  with_region(|region| f(Token(region)))
+}
+
+impl<'c> Token<'c>
+{
+  /// The region of the token.
+  #[inline]
+  pub fn region(&self) -> Region<'c>
+  {
+    self.0
+  }
+}
+
+impl fmt::Debug for Token<'_>
+{
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+  {
+    f.write_str("Token")
+  }
+}
+
+impl<'c, T> TokenCell<'c, T>
+{
+  /// Create a cell of `region` holding `value`.
+  #[inline]
+  pub const fn new(value: T, region: Region<'c>) -> Self
+  {
+    TokenCell { region, value: UnsafeCell::new(value) }
+  }
+
+  /// Returns the contents, giving up the brand.
+  #[inline]
+  pub fn into_inner(self) -> T
+  {
+    self.value.into_inner()
+  }
+}
+
+impl<'c, T: ?Sized> TokenCell<'c, T>
+{
+  /// Returns a cell of `region` over the exclusively borrowed `value`, like `Cell::from_mut`.
+  ///
+  /// The value can then be shared as a cell for as long as it is borrowed, without being copied:
+  ///
+  /// ```
+  /// # use genz::*;
+  /// let mut values = vec![1, 2, 3];
+  /// with_token(|mut token| {
+  ///   let cell = TokenCell::from_mut(&mut values, token.region());
+  ///   let (first, second) = (cell, cell);
+  ///   first.borrow_mut(&mut token).push(4);
+  ///   second.borrow_mut(&mut token).push(5);
+  /// });
+  /// assert_eq!(vec![1, 2, 3, 4, 5], values);
+  /// ```
+  #[inline]
+  pub fn from_mut<'a>(value: &'a mut T, _: Region<'c>) -> &'a Self
+  {
+    // SAFETY: `TokenCell<'c, T>` has the layout of `UnsafeCell<T>`, which has the layout of `T`, and the exclusive
+    // borrow of `value` makes the cell the only way to access it while the returned reference lives.
+    unsafe { &*(value as *mut T as *const Self) }
+  }
+
+  /// The region of the cell.
+  #[inline]
+  pub fn region(&self) -> Region<'c>
+  {
+    self.region
+  }
+
+  /// Returns a shared reference to the contents, for as long as the token is borrowed.
+  #[inline]
+  pub fn borrow<'a>(&'a self, _: &'a Token<'c>) -> &'a T
+  {
+    // SAFETY: mutable references to the contents borrow the only token of `'c` mutably, which the shared borrow here
+    // excludes.
+    unsafe { &*self.value.get() }
+  }
+
+  /// Returns a mutable reference to the contents, for as long as the token is borrowed.
+  #[inline]
+  pub fn borrow_mut<'a>(&'a self, _: &'a mut Token<'c>) -> &'a mut T
+  {
+    // SAFETY: every reference to the contents borrows the only token of `'c`, which is borrowed exclusively here.
+    unsafe { &mut *self.value.get() }
+  }
+
+  /// Returns the cell of the part of the contents which `f` borrows, given a shared borrow of the token to call `f`.
+  ///
+  /// `project!` calls this for a field path checked by `offset_of!`, without `unsafe`.
+  ///
+  /// # Safety
+  ///
+  /// `f` must return a reference to a field of the contents, possibly nested through fields of structs and tuples. A
+  /// value behind a pointer or in a variant of an enum could be dropped by mutating the contents while the returned
+  /// cell is still borrowed.
+  #[inline]
+  pub unsafe fn map_ref<'a, U>(&'a self, _: &Token<'c>, f: impl FnOnce(&T) -> &U) -> &'a TokenCell<'c, U>
+  {
+    let contents = self.value.get();
+    // SAFETY: mutable references to the contents borrow the only token of `'c` mutably, which the shared borrow here
+    // excludes.
+    let part = f(unsafe { &*contents }) as *const U;
+    // SAFETY: the caller ensures that `part` is a field of the contents, so offsetting `contents`, which may mutate
+    // them, by the position of `part` gives a pointer to the field which lives as long as the cell. `TokenCell<'c, U>`
+    // has the layout of `U`.
+    unsafe { &*contents.byte_offset(part.byte_offset_from(contents)).cast() }
+  }
+
+  /// Returns a mutable reference to the contents without the token, like `Cell::get_mut`, since the cell is borrowed
+  /// exclusively.
+  ///
+  /// ```
+  /// # use genz::*;
+  /// with_token(|token| {
+  ///   let mut cell = TokenCell::new(String::from("a"), token.region());
+  ///   cell.get_mut().push('b');
+  ///   assert_eq!("ab", cell.borrow(&token));
+  /// });
+  /// ```
+  #[inline]
+  pub fn get_mut(&mut self) -> &mut T
+  {
+    self.value.get_mut()
+  }
+}
+
+impl<'c, T> TokenCell<'c, [T]>
+{
+  /// Returns a slice of cells over the elements, like `Cell::as_slice_of_cells`.
+  ///
+  /// ```
+  /// # use genz::*;
+  /// let mut values = [1, 2, 3];
+  /// with_token(|mut token| {
+  ///   let cells = TokenCell::from_mut(&mut values[..], token.region()).as_slice_of_cells();
+  ///   let (last, first) = (&cells[2], &cells[0]);
+  ///   *last.borrow_mut(&mut token) += *first.borrow(&token);
+  /// });
+  /// assert_eq!([1, 2, 4], values);
+  /// ```
+  #[inline]
+  pub fn as_slice_of_cells(&self) -> &[TokenCell<'c, T>]
+  {
+    // SAFETY: `TokenCell<'c, T>` has the layout of `T`, so `TokenCell<'c, [T]>` has the layout of `[TokenCell<'c, T>]`,
+    // and every element is borrowed through the same token.
+    unsafe { &*(self as *const Self as *const [TokenCell<'c, T>]) }
+  }
+}
+
+/// Returns the cell of a field of the contents of a `&TokenCell<'c, $ty>`, given a `&Token<'c>`.
+///
+/// Each field can then be borrowed on its own, without turning every field of the type into a cell:
+///
+/// ```
+/// # use genz::*;
+/// struct Particle { position: (i32, i32), speed: i32 }
+///
+/// fn step<'c>(position: &TokenCell<'c, i32>, speed: &TokenCell<'c, i32>, token: &mut Token<'c>)
+/// {
+///   *position.borrow_mut(token) += *speed.borrow(token);
+/// }
+///
+/// with_token(|mut token| {
+///   let particle = TokenCell::new(Particle { position: (0, 0), speed: 3 }, token.region());
+///   let x = project!(&particle, &token, Particle, position.0);
+///   let speed = project!(&particle, &token, Particle, speed);
+///
+///   step(x, speed, &mut token);
+///   step(x, speed, &mut token);
+///   assert_eq!((6, 0), particle.borrow(&token).position);
+/// });
+/// ```
+///
+/// Only fields of structs and tuples can be projected, so the field cannot be dropped while it is borrowed:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// struct Node { next: Box<(u32, u32)> }
+///
+/// with_token(|token| {
+///   let node = TokenCell::new(Node { next: Box::new((1, 2)) }, token.region());
+///   project!(&node, &token, Node, next.0); // fails because the field is behind a pointer
+/// });
+/// ```
+#[macro_export]
+macro_rules! project {
+  ($cell:expr, $token:expr, $ty:ty, $($field:tt).+) => {{
+    let cell: &$crate::TokenCell<'_, $ty> = $cell;
+    let token: &$crate::Token<'_> = $token;
+    // `offset_of!` only accepts paths through fields of structs and tuples
+    let _ = ::core::mem::offset_of!($ty, $($field).+);
+    // SAFETY: the path is accepted by `offset_of!`, so it borrows a field of the contents.
+    unsafe { cell.map_ref(token, |value: &$ty| &value.$($field).+) }
+  }};
+}
+
+impl<T: ?Sized> fmt::Debug for TokenCell<'_, T>
+{
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+  {
+    f.write_str("TokenCell")
+  }
+}
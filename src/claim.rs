@@ -0,0 +1,98 @@
+//! Type markers claimed one at a time at runtime.
+
+use std::{any::TypeId, collections::HashSet};
+use crate::{Region, UniqueType};
+
+/// A registry which hands out type markers for the lifetime `'c` one at a time, at most once per type.
+///
+/// Unlike tuples of markers, types may be claimed lazily, e.g. as plugins are loaded:
+///
+/// ```
+/// # use genz::*;
+/// struct Audio;
+/// struct Video;
+///
+/// with_type_registry(|mut registry| {
+///   let audio = registry.claim::<Audio>().unwrap();
+///   let video = registry.claim::<Video>().unwrap();
+///
+///   assert!(registry.claim::<Audio>().is_none());
+///   assert!(registry.is_claimed::<Video>());
+///   audio.discard();
+///   video.discard();
+/// });
+/// ```
+pub struct TypeRegistry<'c>
+{
+  claimed: HashSet<TypeId>,
+  region: Region<'c>
+}
+
+/// Invoke `f` with an empty registry of type markers for an invariant lifetime.
+#[inline]
+pub fn with_type_registry<Z>(f: impl for <'c> FnOnce(TypeRegistry<'c>) -> Z) -> Z
+{
+  crate::with_region(|region| f(TypeRegistry { claimed: HashSet::new(), region }))
+}
+
+impl<'c> TypeRegistry<'c>
+{
+  /// Returns a marker for `T`, or `None` if `T` has already been claimed.
+  #[inline]
+  pub fn claim<T: ?Sized + 'static>(&mut self) -> Option<UniqueType<'c, T>>
+  {
+    // SAFETY: markers of `'c` are only handed out by this registry, which hands out one per type.
+    self.claimed.insert(TypeId::of::<T>()).then(|| unsafe { UniqueType::unchecked(self.region) })
+  }
+
+  /// Give back the marker for `T`, so `T` may be claimed again.
+  ///
+  /// ```
+  /// # use genz::*;
+  /// struct Plugin;
+  ///
+  /// with_type_registry(|mut registry| {
+  ///   let plugin = registry.claim::<Plugin>().unwrap();
+  ///   registry.release(plugin);
+  ///
+  ///   assert!(!registry.is_claimed::<Plugin>());
+  ///   registry.claim::<Plugin>().unwrap().discard();
+  /// });
+  /// ```
+  ///
+  /// Markers which were consumed, e.g. to create branded evidence, cannot be released, so that evidence stays unique.
+  #[inline]
+  pub fn release<T: ?Sized + 'static>(&mut self, marker: UniqueType<'c, T>)
+  {
+    marker.discard();
+    self.claimed.remove(&TypeId::of::<T>());
+  }
+
+  /// Returns `true` if `T` has been claimed.
+  #[inline]
+  pub fn is_claimed<T: ?Sized + 'static>(&self) -> bool
+  {
+    self.claimed.contains(&TypeId::of::<T>())
+  }
+
+  /// The number of claimed types.
+  #[inline]
+  pub fn len(&self) -> usize
+  {
+    self.claimed.len()
+  }
+
+  /// Returns `true` if no types have been claimed.
+  #[inline]
+  pub fn is_empty(&self) -> bool
+  {
+    self.claimed.is_empty()
+  }
+
+  /// The region of the registry.
+  #[inline]
+  pub fn region(&self) -> Region<'c>
+  {
+    self.region
+  }
+}
@@ -0,0 +1,120 @@
+//! Layered configuration with branded typed settings.
+
+use std::{any::{Any, TypeId}, collections::HashMap, marker::PhantomData};
+use crate::{Region, UniqueType};
+
+/// Evidence that a setting of type `T` is registered with the `ConfigStack<'c>`.
+pub struct Setting<'c, T>(PhantomData<(Region<'c>, fn() -> T)>);
+
+impl<T> Clone for Setting<'_, T>
+{
+  #[inline]
+  fn clone(&self) -> Self
+  {
+    *self
+  }
+}
+
+impl<T> Copy for Setting<'_, T> {}
+
+type Layer = HashMap<TypeId, Box<dyn Any>>;
+
+/// A stack of configuration layers, whose settings are keyed by type and accessed with evidence branded by lifetime `'c`.
+///
+/// Registering a setting consumes its `UniqueType<'c, _>` marker and provides a default in the base layer, so each
+/// setting is registered exactly once and always resolves to a value. Layers pushed on top override settings of those
+/// below:
+///
+/// ```
+/// # use genz::*;
+/// struct Verbose(bool);
+/// struct Threads(usize);
+///
+/// with_types::<(ConfigStack, Verbose, Threads), _>(|_, (config, verbose, threads)| {
+///   let mut config = ConfigStack::new(config);
+///   let verbose = config.register(verbose, Verbose(false));
+///   let threads = config.register(threads, Threads(1));
+///
+///   config.push_layer();
+///   config.set(threads, Threads(8));
+///   assert_eq!((false, 8), (config.get(verbose).0, config.get(threads).0));
+///
+///   config.pop_layer();
+///   assert_eq!(1, config.get(threads).0);
+/// });
+/// ```
+///
+/// Settings of one stack cannot be looked up in another:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// struct Verbose(bool);
+///
+/// with_types::<(ConfigStack, Verbose), _>(|_, (a, verbose)| {
+///   let mut a = ConfigStack::new(a);
+///   let verbose = a.register(verbose, Verbose(false));
+///   with_type(|b| {
+///     ConfigStack::new(b).get(verbose); // fails because `a` and `b` have different lifetimes
+///   });
+/// });
+/// ```
+pub struct ConfigStack<'c>
+{
+  layers: Vec<Layer>,
+  _region: PhantomData<Region<'c>>
+}
+
+impl<'c> ConfigStack<'c>
+{
+  /// Create a stack with an empty base layer.
+  #[inline]
+  pub fn new(_: UniqueType<'c, ConfigStack<'static>>) -> Self
+  {
+    ConfigStack { layers: vec![Layer::new()], _region: PhantomData }
+  }
+
+  /// Register setting `T` with its `default` value in the base layer, returning evidence that it always resolves.
+  pub fn register<T: 'static>(&mut self, _: UniqueType<'c, T>, default: T) -> Setting<'c, T>
+  {
+    self.layers[0].insert(TypeId::of::<T>(), Box::new(default));
+    Setting(PhantomData)
+  }
+
+  /// Push an empty layer, which overrides the layers below it.
+  #[inline]
+  pub fn push_layer(&mut self)
+  {
+    self.layers.push(Layer::new());
+  }
+
+  /// Remove the top layer and its overrides, or return `false` if only the base layer is left.
+  #[inline]
+  pub fn pop_layer(&mut self) -> bool
+  {
+    self.layers.len() > 1 && self.layers.pop().is_some()
+  }
+
+  /// The number of layers, including the base layer.
+  #[inline]
+  pub fn depth(&self) -> usize
+  {
+    self.layers.len()
+  }
+
+  /// Set the value of setting `T` in the top layer, returning the value it replaces in that layer, if any.
+  pub fn set<T: 'static>(&mut self, _: Setting<'c, T>, value: T) -> Option<T>
+  {
+    let layer = self.layers.last_mut().expect("the base layer is never removed");
+    layer.insert(TypeId::of::<T>(), Box::new(value))
+      .map(|old| *old.downcast().expect("values are keyed by their type"))
+  }
+
+  /// Returns the value of setting `T` in the topmost layer which sets it.
+  pub fn get<T: 'static>(&self, _: Setting<'c, T>) -> &T
+  {
+    self.layers.iter().rev()
+      .find_map(|layer| layer.get(&TypeId::of::<T>()))
+      .and_then(|value| value.downcast_ref())
+      .expect("settings are registered in the base layer")
+  }
+}
@@ -0,0 +1,133 @@
+//! References between entries of different branded structures.
+
+use crate::{BiMap, BiMapId, Brand, MutationGuard, VecHandle};
+
+/// A structure whose branded keys always refer to one of its entries.
+pub trait Container
+{
+  /// A key which is branded by the structure.
+  type Key: Copy;
+
+  /// The type of the entries.
+  type Value: ?Sized;
+
+  /// Returns the entry of `key`.
+  fn get(&self, key: Self::Key) -> &Self::Value;
+}
+
+impl<B: Brand, T> Container for MutationGuard<'_, B, T>
+{
+  type Key = VecHandle<B>;
+  type Value = T;
+
+  #[inline]
+  fn get(&self, key: VecHandle<B>) -> &T
+  {
+    MutationGuard::get(self, key)
+  }
+}
+
+impl<B: Brand, V> Container for BiMap<B, V>
+{
+  type Key = BiMapId<B>;
+  type Value = V;
+
+  #[inline]
+  fn get(&self, key: BiMapId<B>) -> &V
+  {
+    BiMap::get(self, key)
+  }
+}
+
+/// A reference from an entry of the structure `F` to an entry of the structure `T`.
+///
+/// Both keys are branded by their structures, so the reference is valid when it is created, and dereferencing it
+/// through either structure cannot fail:
+///
+/// ```
+/// # use genz::*;
+/// let mut payloads = vec![10, 20];
+///
+/// with_guard(&mut payloads, |payloads| {
+///   with_bimap(|mut names| {
+///     let links: Vec<Ref<BiMap<_, _>, MutationGuard<_, _>>> = payloads.handles().map(|payload| {
+///       Ref::new(names.insert(format!("node{}", payload.index())), payload)
+///     }).collect();
+///
+///     let (name, payload) = links[1].resolve(&names, &payloads);
+///     assert_eq!(("node1", 20), (name.as_str(), *payload));
+///   })
+/// });
+/// ```
+///
+/// ```compile_fail
+/// # use genz::*;
+/// let (mut a, mut b) = (vec![1], vec![2]);
+/// with_guard(&mut a, |a| {
+///   with_guard(&mut b, |b| {
+///     let link = Ref::new(a.handle(0).unwrap(), b.handle(0).unwrap());
+///     link.resolve(&a, &a); // fails because the target key belongs to `b`
+///   })
+/// });
+/// ```
+pub struct Ref<F: Container, T: Container>
+{
+  from: F::Key,
+  to: T::Key
+}
+
+impl<F: Container, T: Container> Ref<F, T>
+{
+  /// Create a reference from the entry of `from` to the entry of `to`.
+  #[inline]
+  pub fn new(from: F::Key, to: T::Key) -> Self
+  {
+    Ref { from, to }
+  }
+
+  /// The key of the referring entry.
+  #[inline]
+  pub fn from(&self) -> F::Key
+  {
+    self.from
+  }
+
+  /// The key of the referred entry.
+  #[inline]
+  pub fn to(&self) -> T::Key
+  {
+    self.to
+  }
+
+  /// Returns the referring entry.
+  #[inline]
+  pub fn source<'a>(&self, from: &'a F) -> &'a F::Value
+  {
+    from.get(self.from)
+  }
+
+  /// Returns the referred entry.
+  #[inline]
+  pub fn target<'a>(&self, to: &'a T) -> &'a T::Value
+  {
+    to.get(self.to)
+  }
+
+  /// Returns both entries.
+  #[inline]
+  pub fn resolve<'a, 'b>(&self, from: &'a F, to: &'b T) -> (&'a F::Value, &'b T::Value)
+  {
+    (from.get(self.from), to.get(self.to))
+  }
+}
+
+impl<F: Container, T: Container> Clone for Ref<F, T>
+{
+  #[inline]
+  fn clone(&self) -> Self
+  {
+    *self
+  }
+}
+
+impl<F: Container, T: Container> Copy for Ref<F, T> {}
@@ -0,0 +1,265 @@
+//! Sparse matrices with branded row and column handles.
+
+use std::ops::{AddAssign, Mul};
+use crate::{nonmax::{NonMaxU32, MAX_LEN}, with_region, Brand, DynRegion, Region};
+
+/// A row of the matrix with brand `B`.
+///
+/// The position of the row is stored as a `u32` with a niche, so an `Option<Row<B>>` is no larger than a `Row<B>`:
+///
+/// ```
+/// # use genz::*;
+/// assert_eq!(4, std::mem::size_of::<Option<Row<Region>>>());
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Row<B>(pub(crate) NonMaxU32, pub(crate) B);
+
+impl<B: Brand> Row<B>
+{
+  /// The position of the row in the matrix.
+  #[inline]
+  pub fn index(self) -> usize
+  {
+    self.0.get()
+  }
+
+  /// The position of the row as a `u32`, e.g. for serialization. It is turned back into a row by `Csr::row`.
+  #[inline]
+  pub fn raw(self) -> u32
+  {
+    self.0.raw()
+  }
+
+  /// The brand of the structure the row belongs to.
+  #[inline]
+  pub fn brand(self) -> B
+  {
+    self.1
+  }
+}
+
+/// A column of the matrix with brand `B`, whose position is stored like that of a `Row<B>`.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Col<B>(pub(crate) NonMaxU32, pub(crate) B);
+
+impl<B: Brand> Col<B>
+{
+  /// The position of the column in the matrix.
+  #[inline]
+  pub fn index(self) -> usize
+  {
+    self.0.get()
+  }
+
+  /// The position of the column as a `u32`, e.g. for serialization. It is turned back into a column by `Csr::col`.
+  #[inline]
+  pub fn raw(self) -> u32
+  {
+    self.0.raw()
+  }
+
+  /// The brand of the structure the column belongs to.
+  #[inline]
+  pub fn brand(self) -> B
+  {
+    self.1
+  }
+}
+
+/// A matrix in compressed sparse row format whose row and column handles carry the brand `B`.
+///
+/// The structure of the matrix is validated once when it is created, after which every `Row<B>` and `Col<B>` is known
+/// to be in-bounds and lookups need no further checks. A matrix created by `with_csr` is branded with a `Region<'c>`,
+/// so handles are proven to be of the matrix at compile time:
+///
+/// ```
+/// # use genz::*;
+/// // [[1, 0, 2],
+/// //  [0, 3, 0]]
+/// let y = with_csr(3, vec![0, 2, 3], vec![0, 2, 1], vec![1, 2, 3], |m| {
+///   let mut y = vec![0; m.nrows()];
+///   m.spmv(&[1, 1, 1], &mut y);
+///   y
+/// });
+///
+/// assert_eq!(Some(vec![3, 3]), y);
+///
+/// // `None` is returned because column `3` is out of bounds
+/// assert!(with_csr(3, vec![0, 1], vec![3], vec![1], |_| ()).is_none());
+/// ```
+///
+/// Handles from one matrix cannot be used with another:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// with_csr(1, vec![0, 1], vec![0], vec![1], |a| {
+///   with_csr(1, vec![0, 1], vec![0], vec![1], |b| {
+///     let row = a.rows().next().unwrap();
+///     b.entries(row).count(); // fails because `a` and `b` have different lifetimes
+///   });
+/// });
+/// ```
+///
+/// A matrix created by `Csr::new` is branded with a `DynRegion` instead, so it can be stored without a lifetime, and
+/// using a handle of another matrix panics:
+///
+/// ```should_panic
+/// # use genz::*;
+/// let a = Csr::new(1, vec![0, 1], vec![0], vec![1]).unwrap();
+/// let b = Csr::new(1, vec![0, 1], vec![0], vec![1]).unwrap();
+///
+/// assert_eq!(1, a.entries(a.row(0).unwrap()).count());
+/// b.entries(a.row(0).unwrap()).count(); // panics because the row belongs to `a`
+/// ```
+pub struct Csr<B, T>
+{
+  brand: B,
+  ncols: usize,
+  row_ptr: Vec<usize>,
+  cols: Vec<usize>,
+  values: Vec<T>
+}
+
+/// Attempt to invoke `f` with a sparse matrix branded with an invariant lifetime.
+///
+/// `row_ptr` holds `nrows + 1` offsets into `cols` and `values`, which hold the column and value of every stored entry.
+/// If the offsets are not non-decreasing from `0` to the number of entries, any column is not less than `ncols`, or
+/// there are more than `u32::MAX` rows or columns, `None` is returned.
+pub fn with_csr<T, Z>(
+  ncols: usize,
+  row_ptr: Vec<usize>,
+  cols: Vec<usize>,
+  values: Vec<T>,
+  f: impl for <'c> FnOnce(Csr<Region<'c>, T>) -> Z
+) -> Option<Z>
+{
+  with_region(|region| Csr::validated(region, ncols, row_ptr, cols, values).map(f))
+}
+
+impl<T> Csr<DynRegion, T>
+{
+  /// Attempt to create a sparse matrix branded with a new `DynRegion`.
+  ///
+  /// The arguments are validated as by `with_csr`, and `None` is returned if they are invalid.
+  pub fn new(ncols: usize, row_ptr: Vec<usize>, cols: Vec<usize>, values: Vec<T>) -> Option<Self>
+  {
+    Csr::validated(DynRegion::new(), ncols, row_ptr, cols, values)
+  }
+}
+
+impl<B: Brand, T> Csr<B, T>
+{
+  // `brand` must not be the brand of any other matrix.
+  fn validated(brand: B, ncols: usize, row_ptr: Vec<usize>, cols: Vec<usize>, values: Vec<T>) -> Option<Self>
+  {
+    let valid = row_ptr.first() == Some(&0)
+      && row_ptr.windows(2).all(|w| w[0] <= w[1])
+      && row_ptr.last() == Some(&cols.len())
+      && cols.len() == values.len()
+      && cols.iter().all(|&col| col < ncols)
+      && row_ptr.len() - 1 <= MAX_LEN
+      && ncols <= MAX_LEN;
+
+    valid.then_some(Csr { brand, ncols, row_ptr, cols, values })
+  }
+
+  /// The brand of the matrix.
+  #[inline]
+  pub fn brand(&self) -> B
+  {
+    self.brand
+  }
+
+  /// The number of rows.
+  #[inline]
+  pub fn nrows(&self) -> usize
+  {
+    self.row_ptr.len() - 1
+  }
+
+  /// The number of columns.
+  #[inline]
+  pub fn ncols(&self) -> usize
+  {
+    self.ncols
+  }
+
+  /// The number of stored entries.
+  #[inline]
+  pub fn nnz(&self) -> usize
+  {
+    self.values.len()
+  }
+
+  /// Returns the row at position `index`, or `None` if it is out of bounds.
+  #[inline]
+  pub fn row(&self, index: usize) -> Option<Row<B>>
+  {
+    // SAFETY: `index < nrows <= MAX_LEN`.
+    (index < self.nrows()).then(|| Row(unsafe { NonMaxU32::new_unchecked(index) }, self.brand))
+  }
+
+  /// Returns the column at position `index`, or `None` if it is out of bounds.
+  #[inline]
+  pub fn col(&self, index: usize) -> Option<Col<B>>
+  {
+    // SAFETY: `index < ncols <= MAX_LEN`.
+    (index < self.ncols).then(|| Col(unsafe { NonMaxU32::new_unchecked(index) }, self.brand))
+  }
+
+  /// Iterate over every row of the matrix.
+  #[inline]
+  pub fn rows(&self) -> impl Iterator<Item = Row<B>>
+  {
+    let brand = self.brand;
+    // SAFETY: `index < nrows <= MAX_LEN`.
+    (0 .. self.nrows()).map(move |index| Row(unsafe { NonMaxU32::new_unchecked(index) }, brand))
+  }
+
+  /// Iterate over the columns and values of the entries stored in `row`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `row` is of another matrix, which can only happen for brands checked at runtime.
+  #[inline]
+  pub fn entries(&self, row: Row<B>) -> impl Iterator<Item = (Col<B>, &T)>
+  {
+    assert!(self.brand.same_brand(row.1), "the row is of another matrix");
+    let range = self.range(row);
+    // SAFETY: `range` lies within the stored entries because `row_ptr` was validated on creation.
+    let (cols, values) = unsafe { (self.cols.get_unchecked(range.clone()), self.values.get_unchecked(range)) };
+    let brand = self.brand;
+    // SAFETY: every column is less than `ncols <= MAX_LEN`.
+    cols.iter().map(move |&col| Col(unsafe { NonMaxU32::new_unchecked(col) }, brand)).zip(values)
+  }
+
+  /// Compute `y = A * x`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `x.len()` is not the number of columns or `y.len()` is not the number of rows.
+  pub fn spmv(&self, x: &[T], y: &mut [T])
+    where
+      T: Copy + Default + Mul<Output = T> + AddAssign
+  {
+    assert_eq!(x.len(), self.ncols, "`x` must have one element per column");
+    assert_eq!(y.len(), self.nrows(), "`y` must have one element per row");
+
+    for (row, out) in self.rows().zip(y.iter_mut()) {
+      let mut sum = T::default();
+      for (col, &value) in self.entries(row) {
+        // SAFETY: every column is less than `ncols`, which is the length of `x`.
+        sum += value * unsafe { *x.get_unchecked(col.index()) };
+      }
+      *out = sum;
+    }
+  }
+
+  #[inline]
+  fn range(&self, row: Row<B>) -> std::ops::Range<usize>
+  {
+    // SAFETY: `row < nrows`, so both `row` and `row + 1` are valid offsets into `row_ptr`.
+    let row = row.index();
+    unsafe { *self.row_ptr.get_unchecked(row) .. *self.row_ptr.get_unchecked(row + 1) }
+  }
+}
@@ -0,0 +1,170 @@
+//! Dependency injection whose dependency graphs are checked at compile time.
+
+use std::marker::PhantomData;
+use crate::{Gen, Has, Region, Storable, TypedGenRegistry, UniqueType};
+
+mod sealed
+{
+  pub trait Sealed<S, I> {}
+}
+
+/// A tuple of the storable types a constructor depends on, which the type-level set `S` contains at the indices `I`.
+///
+/// It is implemented for tuples of up to six types, and is sealed.
+pub trait Dependencies<S, I>: sealed::Sealed<S, I>
+{
+  /// The stored values of the dependencies, in the order of the tuple.
+  type Refs<'r>;
+
+  /// Returns the stored values of the dependencies from `registry`, which holds every type of `S`.
+  fn resolve(registry: &TypedGenRegistry) -> Self::Refs<'_>;
+}
+
+/// An injector in the region `'c` holding a stored value per type of the type-level set `S`.
+///
+/// Registering a type consumes its marker, so each type is registered at most once, and requires the set to contain
+/// every type its constructor depends on. Dependencies are therefore registered first, which makes the graph complete
+/// and acyclic by construction, and the `Resolver` of the injector looks up every type of the set without failing:
+///
+/// ```
+/// # use genz::*;
+/// struct Config { name: &'static str }
+/// struct Logger<'c> { lines: Vec<String>, _ty: UniqueType<'c, ()> }
+/// struct Service { greeting: String }
+///
+/// impl Storable for Config { type Generative<'c> = Config; }
+/// impl Storable for Logger<'static> { type Generative<'c> = Logger<'c>; }
+/// impl Storable for Service { type Generative<'c> = Service; }
+///
+/// let resolver = with_types::<(Config, Logger<'static>, Service), _>(|region, (config, logger, service)| {
+///   Injector::new(region)
+///     .register::<(), _, _>(config, |()| Gen::from_value(Config { name: "genz" }))
+///     .register::<(), _, _>(logger, |()| Gen::from_type(|_ty| Logger { lines: Vec::new(), _ty }))
+///     .register::<(Config, Logger<'static>), _, _>(service, |(config, logger)| {
+///       let greeting = format!("hello from {}", config.value().name);
+///       let lines = logger.with_ref(|logger| logger.lines.len());
+///       Gen::from_value(Service { greeting: format!("{greeting} after {lines} lines") })
+///     })
+///     .resolver()
+/// });
+///
+/// assert_eq!("hello from genz after 0 lines", resolver.get::<Service, _>().value().greeting);
+/// ```
+///
+/// A constructor cannot depend on a type which is not registered yet:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// # struct Config;
+/// # struct Service;
+/// # impl Storable for Config { type Generative<'c> = Config; }
+/// # impl Storable for Service { type Generative<'c> = Service; }
+/// with_types::<(Config, Service), _>(|region, (config, service)| {
+///   Injector::new(region)
+///     .register::<(Config,), _, _>(service, |_| Gen::from_value(Service)) // fails because `Config` is missing
+///     .register::<(), _, _>(config, |()| Gen::from_value(Config));
+/// });
+/// ```
+pub struct Injector<'c, S = ()>
+{
+  registry: TypedGenRegistry,
+  _set: PhantomData<(Region<'c>, fn() -> S)>
+}
+
+/// The stored values of a complete `Injector`, whose types form the type-level set `S`.
+pub struct Resolver<S>
+{
+  registry: TypedGenRegistry,
+  _set: PhantomData<fn() -> S>
+}
+
+impl<'c> Injector<'c>
+{
+  /// Create an injector with no registered types.
+  #[inline]
+  pub fn new(_: Region<'c>) -> Self
+  {
+    Injector { registry: TypedGenRegistry::new(), _set: PhantomData }
+  }
+}
+
+impl<'c, S> Injector<'c, S>
+{
+  /// Register the type `Z`, consuming its marker, by invoking `f` with the stored values of the dependencies `D`.
+  #[inline]
+  pub fn register<D, I, Z>(mut self, _: UniqueType<'c, Z>, f: impl FnOnce(D::Refs<'_>) -> Gen<Z>) -> Injector<'c, (Z, S)>
+    where
+      D: Dependencies<S, I>,
+      Z: Storable + 'static
+  {
+    let value = f(D::resolve(&self.registry));
+    self.registry.insert(value);
+    Injector { registry: self.registry, _set: PhantomData }
+  }
+
+  /// Returns the resolver of the registered types.
+  #[inline]
+  pub fn resolver(self) -> Resolver<S>
+  {
+    Resolver { registry: self.registry, _set: PhantomData }
+  }
+}
+
+impl<S> Resolver<S>
+{
+  /// Returns the stored value of type `Z`.
+  #[inline]
+  pub fn get<Z: Storable + 'static, I>(&self) -> &Gen<Z>
+    where
+      S: Has<Z, I>
+  {
+    self.registry.get().expect("the injector holds every type of its set")
+  }
+
+  /// Returns the stored value of type `Z` mutably.
+  #[inline]
+  pub fn get_mut<Z: Storable + 'static, I>(&mut self) -> &mut Gen<Z>
+    where
+      S: Has<Z, I>
+  {
+    self.registry.get_mut().expect("the injector holds every type of its set")
+  }
+
+  /// Returns the registry of the stored values, giving up the set.
+  #[inline]
+  pub fn into_registry(self) -> TypedGenRegistry
+  {
+    self.registry
+  }
+}
+
+macro_rules! dependencies {
+  ($($dep:ident $index:ident),*) => {
+    impl<S, $($dep, $index),*> sealed::Sealed<S, ($($index,)*)> for ($($dep,)*)
+      where
+        $(S: Has<$dep, $index>),*
+    {}
+
+    impl<S, $($dep: Storable + 'static, $index),*> Dependencies<S, ($($index,)*)> for ($($dep,)*)
+      where
+        $(S: Has<$dep, $index>),*
+    {
+      type Refs<'r> = ($(&'r Gen<$dep>,)*);
+
+      #[inline]
+      #[allow(unused_variables, clippy::unused_unit)]
+      fn resolve(registry: &TypedGenRegistry) -> Self::Refs<'_>
+      {
+        ($(registry.get::<$dep>().expect("dependencies are registered before their dependents"),)*)
+      }
+    }
+  };
+}
+
+dependencies!();
+dependencies!(A IA);
+dependencies!(A IA, B IB);
+dependencies!(A IA, B IB, C IC);
+dependencies!(A IA, B IB, C IC, D ID);
+dependencies!(A IA, B IB, C IC, D ID, E IE);
+dependencies!(A IA, B IB, C IC, D ID, E IE, F IF);
@@ -0,0 +1,108 @@
+//! Ownership of buffers shared with DMA hardware.
+
+use std::{marker::PhantomData, mem::ManuallyDrop, ops::DerefMut};
+use crate::Region;
+
+/// The channel performing transfers on the buffer owned by the `DmaToken<'c, B>`.
+///
+/// The channel is the only way to produce the `DmaDone<'c>` evidence needed to end a transfer, and is typically handed
+/// to the code servicing the transfer-complete interrupt.
+pub struct DmaChannel<'c>(PhantomData<Region<'c>>);
+
+/// Exclusive access to a buffer which is not currently used by DMA hardware.
+pub struct DmaToken<'c, B>(B, PhantomData<Region<'c>>);
+
+/// A buffer which is owned by DMA hardware until the transfer completes.
+///
+/// The buffer cannot be accessed until the transfer is finished. Dropping an unfinished transfer leaks the buffer, as
+/// the hardware may still be writing to it.
+pub struct DmaTransfer<'c, B>(ManuallyDrop<B>, PhantomData<Region<'c>>);
+
+/// Evidence that the transfer on the `DmaChannel<'c>` has completed.
+pub struct DmaDone<'c>(PhantomData<Region<'c>>);
+
+/// Invoke `f` with a DMA channel and a token for `buffer` which are branded with an invariant lifetime.
+///
+/// Starting a transfer consumes the token, and only the matching channel can end it, so the buffer cannot be touched
+/// while the hardware owns it:
+///
+/// ```
+/// # use genz::*;
+/// let buffer = with_dma(vec![0u8; 4], |channel, token| {
+///   let transfer = token.start(|buffer| {
+///     // program the hardware with `buffer.as_mut_ptr()` and `buffer.len()`
+///     buffer[0] = 1;
+///   });
+///
+///   // SAFETY: the simulated hardware above completed synchronously
+///   let done = unsafe { channel.complete() };
+///   transfer.finish(done).into_inner()
+/// });
+///
+/// assert_eq!(vec![1, 0, 0, 0], buffer);
+/// ```
+///
+/// ```compile_fail
+/// # use genz::*;
+/// with_dma(vec![0u8; 4], |_, token| {
+///   let transfer = token.start(|_| {});
+///   token.buffer()[0] = 1; // fails because the token was consumed by the transfer
+/// });
+/// ```
+#[inline]
+pub fn with_dma<B: DerefMut, Z>(buffer: B, f: impl for <'c> FnOnce(DmaChannel<'c>, DmaToken<'c, B>) -> Z) -> Z
+{
+  f(DmaChannel(PhantomData), DmaToken(buffer, PhantomData))
+}
+
+impl<'c> DmaChannel<'c>
+{
+  /// Produce evidence that the transfer on this channel has completed.
+  ///
+  /// # Safety
+  ///
+  /// The hardware must have finished accessing the buffer of the current transfer, e.g. because this is called from the
+  /// transfer-complete interrupt.
+  #[inline]
+  pub unsafe fn complete(&self) -> DmaDone<'c>
+  {
+    DmaDone(PhantomData)
+  }
+}
+
+impl<'c, B: DerefMut> DmaToken<'c, B>
+{
+  /// Returns a mutable reference to the buffer.
+  #[inline]
+  pub fn buffer(&mut self) -> &mut B::Target
+  {
+    &mut self.0
+  }
+
+  /// Start a transfer by invoking `f` with the buffer, handing ownership of the buffer to the hardware.
+  ///
+  /// The target of `B` must not move when `B` is moved, as is the case for references, `Box` and `Vec`.
+  #[inline]
+  pub fn start(mut self, f: impl FnOnce(&mut B::Target)) -> DmaTransfer<'c, B>
+  {
+    f(&mut self.0);
+    DmaTransfer(ManuallyDrop::new(self.0), PhantomData)
+  }
+
+  /// Give up the token, returning the buffer.
+  #[inline]
+  pub fn into_inner(self) -> B
+  {
+    self.0
+  }
+}
+
+impl<'c, B> DmaTransfer<'c, B>
+{
+  /// End the transfer, returning the token for the buffer.
+  #[inline]
+  pub fn finish(self, _: DmaDone<'c>) -> DmaToken<'c, B>
+  {
+    DmaToken(ManuallyDrop::into_inner(self.0), PhantomData)
+  }
+}
@@ -0,0 +1,142 @@
+//! Brands checked at runtime.
+
+use std::{marker::PhantomData, num::NonZeroU64, sync::atomic::{AtomicU64, Ordering}};
+use crate::{DuplicateType, StaticTuple};
+
+/// A region identified by a runtime id rather than a lifetime.
+///
+/// Every call to `DynRegion::new` returns a region which is distinct from every other region, so values tagged with a
+/// `DynRegion` can be checked for a common origin without carrying a lifetime parameter:
+///
+/// ```
+/// # use genz::*;
+/// struct Handle {
+///   region: DynRegion,
+///   index: usize
+/// }
+///
+/// let a = DynRegion::new();
+/// let b = DynRegion::new();
+/// let handle = Handle { region: a, index: 0 };
+///
+/// assert!(handle.region == a);
+/// assert!(handle.region != b);
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct DynRegion(NonZeroU64);
+
+impl DynRegion
+{
+  /// Create a region which is distinct from every other region.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `u64::MAX` regions have already been created.
+  #[inline]
+  #[allow(clippy::new_without_default)]
+  pub fn new() -> Self
+  {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+    DynRegion(NonZeroU64::new(NEXT.fetch_add(1, Ordering::Relaxed)).expect("region ids are exhausted"))
+  }
+
+  /// The id of the region.
+  #[inline]
+  pub fn id(self) -> u64
+  {
+    self.0.get()
+  }
+}
+
+/// A marker for a type which is unique within a `DynRegion`.
+///
+/// Unlike `UniqueType`, which region the marker belongs to is only known at runtime:
+///
+/// ```
+/// # use genz::*;
+/// let (region, (t1, t2)) = try_dyn_types::<(u8, u16)>().unwrap();
+///
+/// assert!(t1.region() == region && t2.region() == region);
+/// assert!(dyn_type::<u8>().region() != region);
+///
+/// // an error is returned because `u8` is repeated in the tuple
+/// assert!(try_dyn_types::<(u8, u8)>().is_err());
+/// ```
+#[must_use = "dropping a type marker gives up the uniqueness of its type; use `discard` if this is intended"]
+pub struct DynUnique<T>(DynRegion, PhantomData<T>);
+
+impl<T> DynUnique<T>
+{
+  /// The region for which `T` is unique.
+  #[inline]
+  pub fn region(&self) -> DynRegion
+  {
+    self.0
+  }
+
+  /// Explicitly drop the marker.
+  #[inline]
+  pub fn discard(self) {}
+}
+
+impl<T> From<DynUnique<T>> for DynRegion
+{
+  #[inline]
+  fn from(value: DynUnique<T>) -> Self {
+    value.0
+  }
+}
+
+/// Create a type marker which is unique for a new region.
+#[inline]
+pub fn dyn_type<T>() -> DynUnique<T>
+{
+  DynUnique(DynRegion::new(), PhantomData)
+}
+
+/// Attempt to create a new region and a tuple of type markers that are unique for that region.
+///
+/// If any types in the tuple are duplicates, a `DuplicateType` error is returned.
+#[inline]
+pub fn try_dyn_types<Types: TryGenDynTuple>() -> Result<(DynRegion, Types::DynTuple), DuplicateType>
+{
+  let region = DynRegion::new();
+  Types::try_gen_dyn_tuple(region).map(|types| (region, types))
+}
+
+/// A trait for creating tuples of type markers which are unique for a `DynRegion`.
+pub trait TryGenDynTuple: StaticTuple
+{
+  /// A tuple of type markers which are unique for a `DynRegion`.
+  type DynTuple;
+
+  /// Returns a tuple of type markers which are unique for `region` if every type in `Self` is distinct.
+  fn try_gen_dyn_tuple(region: DynRegion) -> Result<Self::DynTuple, DuplicateType>;
+}
+
+macro_rules! gen_dyn_tuple {
+    ($($tt:ident),+) => {
+      impl<$($tt),+> TryGenDynTuple for ($($tt,)+)
+        where
+          $($tt: 'static),+
+      {
+        type DynTuple = ($(DynUnique<$tt>,)+);
+
+        #[inline]
+        fn try_gen_dyn_tuple(region: DynRegion) -> Result<Self::DynTuple, DuplicateType>
+        {
+          <($($tt,)+)>::check_distinct().map(|()| ($(DynUnique(region, PhantomData::<$tt>),)+))
+        }
+      }
+    };
+}
+
+gen_dyn_tuple!(T0, T1);
+gen_dyn_tuple!(T0, T1, T2);
+gen_dyn_tuple!(T0, T1, T2, T3);
+gen_dyn_tuple!(T0, T1, T2, T3, T4);
+gen_dyn_tuple!(T0, T1, T2, T3, T4, T5);
+gen_dyn_tuple!(T0, T1, T2, T3, T4, T5, T6);
+gen_dyn_tuple!(T0, T1, T2, T3, T4, T5, T6, T7);
+gen_dyn_tuple!(T0, T1, T2, T3, T4, T5, T6, T7, T8);
+gen_dyn_tuple!(T0, T1, T2, T3, T4, T5, T6, T7, T8, T9);
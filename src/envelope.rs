@@ -0,0 +1,97 @@
+//! Moving stored values between regions through brand-free forms.
+
+use crate::{Gen, GenSlot, Region, Storable};
+
+/// A stored value which can be taken out of its region in a brand-free form, and rebuilt in another.
+pub trait Export: Storable
+{
+  /// The brand-free form of the value, e.g. a serialization or a deep clone.
+  type Sealed: Send + 'static;
+
+  /// Returns the brand-free form of `value`.
+  fn export<'c>(value: &Self::Generative<'c>) -> Self::Sealed;
+
+  /// Rebuild a value in the region `'c` from its brand-free form.
+  fn import<'c>(region: Region<'c>, sealed: Self::Sealed) -> Self::Generative<'c>;
+}
+
+/// The brand-free form of a stored value of `Z`, which may be sent across threads, and opened under a new region on the
+/// other side:
+///
+/// ```
+/// # use genz::*;
+/// struct Interner<'c>(Region<'c>, Vec<String>);
+///
+/// impl Storable for Interner<'static> {
+///   type Generative<'c> = Interner<'c>;
+/// }
+///
+/// impl Export for Interner<'static> {
+///   type Sealed = Vec<String>;
+///
+///   fn export<'c>(interner: &Interner<'c>) -> Vec<String>
+///   {
+///     interner.1.clone()
+///   }
+///
+///   fn import<'c>(region: Region<'c>, strings: Vec<String>) -> Interner<'c>
+///   {
+///     Interner(region, strings)
+///   }
+/// }
+///
+/// let interner = Gen::<Interner>::from_type::<()>(|ty| Interner(ty.into(), vec!["a".to_owned()]));
+/// let envelope = Envelope::seal(&interner);
+///
+/// let opened = std::thread::spawn(move || envelope.open()).join().unwrap();
+/// assert_eq!(vec!["a"], opened.with_ref(|interner| interner.1.clone()));
+/// ```
+pub struct Envelope<Z: Export>(Z::Sealed);
+
+impl<Z: Export> Envelope<Z>
+{
+  /// Seal the brand-free form of the stored value.
+  #[inline]
+  pub fn seal(gen: &Gen<Z>) -> Self
+  {
+    gen.with_ref(|value| Self::seal_ref(value))
+  }
+
+  /// Seal the brand-free form of a value in any region.
+  #[inline]
+  pub fn seal_ref(value: &Z::Generative<'_>) -> Self
+  {
+    Envelope(Z::export(value))
+  }
+
+  /// Open the envelope under a new region, returning the stored value.
+  #[inline]
+  pub fn open(self) -> Gen<Z>
+  {
+    GenSlot::with(|slot| {
+      let region = slot.region();
+      slot.fill(Z::import(region, self.0))
+    })
+  }
+
+  /// Open the envelope under a new region, and invoke `f` with the value.
+  #[inline]
+  pub fn open_with<R>(self, f: impl for <'c> FnOnce(Z::Generative<'c>) -> R) -> R
+  {
+    self.open().with(f)
+  }
+
+  /// Returns the brand-free form of the value.
+  #[inline]
+  pub fn into_sealed(self) -> Z::Sealed
+  {
+    self.0
+  }
+
+  /// Wrap a brand-free form of the value, e.g. one received from another process.
+  #[inline]
+  pub fn from_sealed(sealed: Z::Sealed) -> Self
+  {
+    Envelope(sealed)
+  }
+}
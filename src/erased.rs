@@ -0,0 +1,84 @@
+//! Stored values with their storable type erased.
+
+use std::any::{Any, TypeId};
+use crate::{Gen, Storable};
+
+/// A stored value whose storable type is erased, so values of different types may be kept together.
+///
+/// The value is recovered by naming its storable type. Each access still opens the value with a fresh invariant
+/// lifetime, so erasing the type does not erase the brand:
+///
+/// ```
+/// # use genz::*;
+/// struct Names<'c>(UniqueType<'c, u8>, Vec<&'static str>);
+///
+/// impl Storable for Names<'static> {
+///   type Generative<'c> = Names<'c>;
+/// }
+///
+/// let mut registry = vec![
+///   GenAny::new(Gen::<Names>::from_type(|ty| Names(ty, vec!["a"]))),
+///   GenAny::new(Gen::<Region>::new_region())
+/// ];
+///
+/// assert_eq!(Some(()), registry[0].with_mut::<Names, _>(|names| names.1.push("b")));
+/// assert_eq!(None, registry[1].with_ref::<Names, _>(|names| names.1.len()));
+///
+/// let names = registry.remove(0).downcast::<Names>().ok().unwrap();
+/// assert_eq!(2, names.with_ref(|names| names.1.len()));
+/// ```
+pub struct GenAny(Box<dyn Any>);
+
+impl GenAny
+{
+  /// Erase the storable type of `gen`.
+  #[inline]
+  pub fn new<Z: Storable + 'static>(gen: Gen<Z>) -> Self
+  {
+    GenAny(Box::new(gen))
+  }
+
+  /// Returns `true` if the storable type of the value is `Z`.
+  #[inline]
+  pub fn is<Z: Storable + 'static>(&self) -> bool
+  {
+    self.0.is::<Gen<Z>>()
+  }
+
+  /// The `TypeId` of the erased `Gen<Z>`.
+  #[inline]
+  pub fn type_id(&self) -> TypeId
+  {
+    (*self.0).type_id()
+  }
+
+  /// Convert to the stored value if its storable type is `Z`, or return `self` otherwise.
+  #[inline]
+  pub fn downcast<Z: Storable + 'static>(self) -> Result<Gen<Z>, Self>
+  {
+    self.0.downcast().map(|gen| *gen).map_err(GenAny)
+  }
+
+  /// Invoke `f` with a reference to the value if its storable type is `Z`, or return `None` otherwise.
+  #[inline]
+  pub fn with_ref<Z: Storable + 'static, R>(&self, f: impl for <'c> FnOnce(&Z::Generative<'c>) -> R) -> Option<R>
+  {
+    self.0.downcast_ref::<Gen<Z>>().map(|gen| gen.with_ref(f))
+  }
+
+  /// Invoke `f` with a mutable reference to the value if its storable type is `Z`, or return `None` otherwise.
+  #[inline]
+  pub fn with_mut<Z: Storable + 'static, R>(&mut self, f: impl for <'c> FnOnce(&mut Z::Generative<'c>) -> R) -> Option<R>
+  {
+    self.0.downcast_mut::<Gen<Z>>().map(|gen| gen.with_mut(f))
+  }
+}
+
+impl<Z: Storable + 'static> From<Gen<Z>> for GenAny
+{
+  #[inline]
+  fn from(gen: Gen<Z>) -> Self
+  {
+    GenAny::new(gen)
+  }
+}
@@ -0,0 +1,67 @@
+//! Errors reported when types fail to be distinct.
+
+use std::fmt;
+
+/// The error returned when a tuple which must contain distinct types contains a repeated type.
+///
+/// ```
+/// # use genz::*;
+/// let err = try_with_types::<(u8, u16, u8), _>(|_, _| ()).unwrap_err();
+///
+/// assert_eq!("u8", err.type_name());
+/// assert_eq!((0, 2), err.positions());
+/// assert_eq!("type `u8` appears at positions 0 and 2 of the tuple", err.to_string());
+///
+/// let err = err.context("registering plugins");
+/// assert_eq!("registering plugins: type `u8` appears at positions 0 and 2 of the tuple", err.to_string());
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct DuplicateType
+{
+  type_name: &'static str,
+  positions: (usize, usize),
+  context: Option<&'static str>
+}
+
+impl DuplicateType
+{
+  #[inline]
+  pub(crate) fn new(type_name: &'static str, first: usize, second: usize) -> Self
+  {
+    DuplicateType { type_name, positions: (first, second), context: None }
+  }
+
+  /// The name of the repeated type, as given by `std::any::type_name`.
+  #[inline]
+  pub fn type_name(&self) -> &'static str
+  {
+    self.type_name
+  }
+
+  /// The positions of the first two occurrences of the repeated type in the tuple.
+  #[inline]
+  pub fn positions(&self) -> (usize, usize)
+  {
+    self.positions
+  }
+
+  /// Attach a description of what was being attempted, which is prepended to the message.
+  #[inline]
+  pub fn context(self, context: &'static str) -> Self
+  {
+    DuplicateType { context: Some(context), ..self }
+  }
+}
+
+impl fmt::Display for DuplicateType
+{
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+  {
+    if let Some(context) = self.context {
+      write!(f, "{}: ", context)?;
+    }
+    write!(f, "type `{}` appears at positions {} and {} of the tuple", self.type_name, self.positions.0, self.positions.1)
+  }
+}
+
+impl std::error::Error for DuplicateType {}
@@ -0,0 +1,175 @@
+//! Prefix sums over branded positions.
+
+use std::ops::AddAssign;
+use crate::{with_region, Brand, DynRegion, Region};
+
+/// A position in the `Fenwick<B, _>`.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct FenwickIndex<B>(usize, B);
+
+impl<B: Brand> FenwickIndex<B>
+{
+  /// The position of the value in the tree.
+  #[inline]
+  pub fn index(self) -> usize
+  {
+    self.0
+  }
+
+  /// The brand of the structure the position belongs to.
+  #[inline]
+  pub fn brand(self) -> B
+  {
+    self.1
+  }
+}
+
+/// A Fenwick tree of values, whose positions carry the brand `B`.
+///
+/// A position is checked once when its `FenwickIndex<B>` is created, after which updates and prefix sums need no
+/// bounds checks. A tree created by `with_fenwick` is branded with a `Region<'c>`, so positions are proven to be of the
+/// tree at compile time:
+///
+/// ```
+/// # use genz::*;
+/// let sums = with_fenwick(vec![1, 2, 3, 4], |mut tree| {
+///   let second = tree.index(1).unwrap();
+///   tree.update(second, 10);
+///
+///   tree.indices().map(|index| tree.prefix_sum(index)).collect::<Vec<_>>()
+/// });
+///
+/// assert_eq!(vec![1, 13, 16, 20], sums);
+/// ```
+///
+/// Positions of one tree cannot be used with another:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// with_fenwick(vec![1], |a| {
+///   with_fenwick(vec![1], |b| {
+///     b.prefix_sum(a.index(0).unwrap()); // fails because `a` and `b` have different lifetimes
+///   });
+/// });
+/// ```
+///
+/// A tree created by `Fenwick::new` is branded with a `DynRegion` instead, and using a position of another tree panics:
+///
+/// ```should_panic
+/// # use genz::*;
+/// let a = Fenwick::new(vec![1]);
+/// let b = Fenwick::new(vec![1]);
+/// b.prefix_sum(a.index(0).unwrap()); // panics because the position belongs to `a`
+/// ```
+pub struct Fenwick<B, T>
+{
+  brand: B,
+  tree: Vec<T>
+}
+
+/// Invoke `f` with a Fenwick tree of `values`, branded with an invariant lifetime.
+#[inline]
+pub fn with_fenwick<T: Copy + AddAssign, Z>(values: Vec<T>, f: impl for <'c> FnOnce(Fenwick<Region<'c>, T>) -> Z) -> Z
+{
+  with_region(|region| f(Fenwick::build(region, values)))
+}
+
+impl<T: Copy + AddAssign> Fenwick<DynRegion, T>
+{
+  /// Create a Fenwick tree of `values`, branded with a new `DynRegion`.
+  #[inline]
+  pub fn new(values: Vec<T>) -> Self
+  {
+    Fenwick::build(DynRegion::new(), values)
+  }
+}
+
+impl<B: Brand, T: Copy + AddAssign> Fenwick<B, T>
+{
+  // `brand` must not be the brand of any other tree.
+  fn build(brand: B, mut tree: Vec<T>) -> Self
+  {
+    for i in 1 ..= tree.len() {
+      let parent = i + (i & i.wrapping_neg());
+      if parent <= tree.len() {
+        let child = tree[i - 1];
+        tree[parent - 1] += child;
+      }
+    }
+    Fenwick { brand, tree }
+  }
+
+  /// The brand of the tree.
+  #[inline]
+  pub fn brand(&self) -> B
+  {
+    self.brand
+  }
+
+  /// The number of values.
+  #[inline]
+  pub fn len(&self) -> usize
+  {
+    self.tree.len()
+  }
+
+  /// Returns `true` if there are no values.
+  #[inline]
+  pub fn is_empty(&self) -> bool
+  {
+    self.tree.is_empty()
+  }
+
+  /// Returns the position `index`, or `None` if it is out of bounds.
+  #[inline]
+  pub fn index(&self, index: usize) -> Option<FenwickIndex<B>>
+  {
+    (index < self.tree.len()).then_some(FenwickIndex(index, self.brand))
+  }
+
+  /// Iterate over every position of the tree.
+  #[inline]
+  pub fn indices(&self) -> impl Iterator<Item = FenwickIndex<B>>
+  {
+    let brand = self.brand;
+    (0 .. self.tree.len()).map(move |index| FenwickIndex(index, brand))
+  }
+
+  /// Add `delta` to the value at `index`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `index` is of another tree, which can only happen for brands checked at runtime.
+  #[inline]
+  pub fn update(&mut self, index: FenwickIndex<B>, delta: T)
+  {
+    assert!(self.brand.same_brand(index.1), "the position is of another tree");
+    let mut i = index.0 + 1;
+    while i <= self.tree.len() {
+      // SAFETY: `1 <= i <= len`.
+      unsafe { *self.tree.get_unchecked_mut(i - 1) += delta };
+      i += i & i.wrapping_neg();
+    }
+  }
+
+  /// Returns the sum of the values up to and including `index`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `index` is of another tree, which can only happen for brands checked at runtime.
+  #[inline]
+  pub fn prefix_sum(&self, index: FenwickIndex<B>) -> T
+  {
+    assert!(self.brand.same_brand(index.1), "the position is of another tree");
+    let mut i = index.0 + 1;
+    // SAFETY: `index.0 < len`, so `1 <= i <= len` here and below.
+    let mut sum = unsafe { *self.tree.get_unchecked(i - 1) };
+    i &= i - 1;
+    while i > 0 {
+      // SAFETY: see above.
+      sum += unsafe { *self.tree.get_unchecked(i - 1) };
+      i &= i - 1;
+    }
+    sum
+  }
+}
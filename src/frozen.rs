@@ -0,0 +1,381 @@
+//! Frozen snapshots branded with invariant lifetimes, and brand-free edit scripts between them.
+
+use std::{fmt, ops::{Deref, Range}};
+use crate::{with_region, Region};
+
+/// An immutable snapshot of values, branded with lifetime `'c`.
+///
+/// Snapshots taken in different regions have different brands, but can still be compared by `diff` and `merge`, whose
+/// edit scripts carry no brand and so can be sent to and applied anywhere:
+///
+/// ```
+/// # use genz::*;
+/// let script = with_frozen_vec(vec!["a", "b", "c"], |old| {
+///   with_frozen_vec(vec!["a", "x", "c", "d"], |new| diff(&old, &new))
+/// });
+///
+/// let edits = [Edit::Keep(1), Edit::Delete(1), Edit::Insert(vec!["x"]), Edit::Keep(1), Edit::Insert(vec!["d"])];
+/// assert_eq!(&edits, script.edits());
+/// assert_eq!(Some(vec!["a", "x", "c", "d"]), script.apply(&["a", "b", "c"]));
+///
+/// // `None` is returned because the script was computed for a base of another length
+/// assert_eq!(None, script.apply(&["a"]));
+/// ```
+///
+/// Snapshots cannot escape their region:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// let snapshot = with_frozen_vec(vec![1], |snapshot| snapshot); // fails because the snapshot escapes
+/// ```
+pub struct FrozenVec<'c, T>
+{
+  values: Box<[T]>,
+  region: Region<'c>
+}
+
+/// An operation of a `Diff`, in terms of positions of its base.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Edit<T>
+{
+  /// Keep the next `n` values of the base.
+  Keep(usize),
+  /// Skip the next `n` values of the base.
+  Delete(usize),
+  /// Insert values which are not in the base.
+  Insert(Vec<T>)
+}
+
+/// A brand-free edit script, which turns a base sequence into another.
+///
+/// Runs of each kind of edit are coalesced, and a deletion always precedes an insertion at the same position.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Diff<T>
+{
+  edits: Vec<Edit<T>>
+}
+
+/// The error returned when two edit scripts of the same base change overlapping parts of it in different ways.
+///
+/// ```
+/// # use genz::*;
+/// with_frozen_vec(vec![1, 2, 3], |base| {
+///   with_frozen_vec(vec![1, 20, 3], |ours| {
+///     with_frozen_vec(vec![1, 21, 3], |theirs| {
+///       let err = merge(&base, &ours, &theirs).unwrap_err();
+///       assert_eq!(1 .. 2, err.range());
+///       assert_eq!("both sides change the values at positions 1 .. 2 of the base", err.to_string());
+///     })
+///   })
+/// });
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct MergeConflict
+{
+  start: usize,
+  end: usize
+}
+
+// A step of the path found by `diff`.
+enum Step<T>
+{
+  Keep,
+  Delete,
+  Insert(T)
+}
+
+// A maximal run of changes, replacing `start .. end` of the base with `insert`.
+#[derive(PartialEq)]
+struct Hunk<'s, T>
+{
+  start: usize,
+  end: usize,
+  insert: &'s [T]
+}
+
+/// Invoke `f` with a snapshot of `values`, branded with an invariant lifetime.
+#[inline]
+pub fn with_frozen_vec<T, Z>(values: Vec<T>, f: impl for <'c> FnOnce(FrozenVec<'c, T>) -> Z) -> Z
+{
+  with_region(|region| f(FrozenVec { values: values.into_boxed_slice(), region }))
+}
+
+/// Returns an edit script which turns `old` into `new`, with as few deleted and inserted values as possible.
+///
+/// The snapshots may have different brands. The script is computed with Myers' algorithm, in `O((n + m) d)` time for
+/// `d` differences.
+pub fn diff<'a, 'b, T: PartialEq + Clone>(old: &FrozenVec<'a, T>, new: &FrozenVec<'b, T>) -> Diff<T>
+{
+  let (old, new) = (&*old.values, &*new.values);
+  let (n, m) = (old.len() as isize, new.len() as isize);
+  let offset = n + m + 1;
+  let mut furthest = vec![0; 2 * offset as usize + 1];
+  let mut trace = Vec::new();
+
+  // find the furthest reaching path on each diagonal `k = x - y` with `d` differences, until one reaches the end
+  'search: for d in 0 ..= n + m {
+    trace.push(furthest.clone());
+    for k in (-d ..= d).step_by(2) {
+      let at = |k: isize| (k + offset) as usize;
+      let mut x = if k == -d || (k != d && furthest[at(k - 1)] < furthest[at(k + 1)]) {
+        furthest[at(k + 1)]
+      } else {
+        furthest[at(k - 1)] + 1
+      };
+      let mut y = x - k;
+      while x < n && y < m && old[x as usize] == new[y as usize] {
+        (x, y) = (x + 1, y + 1);
+      }
+      furthest[at(k)] = x;
+      if x >= n && y >= m {
+        break 'search;
+      }
+    }
+  }
+
+  // walk the path back from the end, collecting its steps in reverse
+  let mut reversed = Vec::new();
+  let (mut x, mut y) = (n, m);
+  for (d, furthest) in trace.iter().enumerate().rev() {
+    let (d, k) = (d as isize, x - y);
+    let at = |k: isize| (k + offset) as usize;
+    let k = if k == -d || (k != d && furthest[at(k - 1)] < furthest[at(k + 1)]) { k + 1 } else { k - 1 };
+    let (from_x, from_y) = (furthest[at(k)], furthest[at(k)] - k);
+    while x > from_x && y > from_y {
+      reversed.push(Step::Keep);
+      (x, y) = (x - 1, y - 1);
+    }
+    if d > 0 {
+      reversed.push(if x == from_x { Step::Insert(new[y as usize - 1].clone()) } else { Step::Delete });
+    }
+    (x, y) = (from_x, from_y);
+  }
+
+  let mut diff = Diff { edits: Vec::new() };
+  let (mut deleted, mut inserted) = (0, Vec::new());
+  for step in reversed.into_iter().rev() {
+    match step {
+      Step::Keep => {
+        diff.change(&mut deleted, &mut inserted);
+        diff.keep(1);
+      }
+      Step::Delete => deleted += 1,
+      Step::Insert(value) => inserted.push(value)
+    }
+  }
+  diff.change(&mut deleted, &mut inserted);
+  diff
+}
+
+/// Returns an edit script which applies the changes of both `ours` and `theirs` to `base`, or an error if they change
+/// overlapping parts of it in different ways.
+///
+/// The three snapshots may have different brands:
+///
+/// ```
+/// # use genz::*;
+/// with_frozen_vec(vec![1, 2, 3, 4], |base| {
+///   with_frozen_vec(vec![1, 20, 3, 4], |ours| {
+///     with_frozen_vec(vec![1, 2, 3, 4, 5], |theirs| {
+///       let merged = merge(&base, &ours, &theirs).unwrap();
+///       assert_eq!(Some(vec![1, 20, 3, 4, 5]), merged.apply(&base));
+///     })
+///   })
+/// });
+/// ```
+pub fn merge<'a, 'b, 'c, T: PartialEq + Clone>(base: &FrozenVec<'a, T>, ours: &FrozenVec<'b, T>, theirs: &FrozenVec<'c, T>)
+  -> Result<Diff<T>, MergeConflict>
+{
+  diff(base, ours).merge(&diff(base, theirs))
+}
+
+impl<'c, T> FrozenVec<'c, T>
+{
+  /// The region of the snapshot.
+  #[inline]
+  pub fn region(&self) -> Region<'c>
+  {
+    self.region
+  }
+
+  /// The values of the snapshot.
+  #[inline]
+  pub fn as_slice(&self) -> &[T]
+  {
+    &self.values
+  }
+
+  /// Returns the values, giving up the brand.
+  #[inline]
+  pub fn into_vec(self) -> Vec<T>
+  {
+    self.values.into_vec()
+  }
+}
+
+impl<T> Deref for FrozenVec<'_, T>
+{
+  type Target = [T];
+
+  #[inline]
+  fn deref(&self) -> &[T]
+  {
+    &self.values
+  }
+}
+
+impl<T> Diff<T>
+{
+  /// The edits of the script.
+  #[inline]
+  pub fn edits(&self) -> &[Edit<T>]
+  {
+    &self.edits
+  }
+
+  /// Returns `true` if the script keeps its base unchanged.
+  #[inline]
+  pub fn is_empty(&self) -> bool
+  {
+    self.edits.iter().all(|edit| matches!(edit, Edit::Keep(_)))
+  }
+
+  /// The length of the base the script applies to.
+  pub fn base_len(&self) -> usize
+  {
+    self.edits.iter().map(|edit| match edit {
+      Edit::Keep(n) | Edit::Delete(n) => *n,
+      Edit::Insert(_) => 0
+    }).sum()
+  }
+
+  /// Apply the script to `base`, or return `None` if `base` does not have the length of the base of the script.
+  pub fn apply(&self, base: &[T]) -> Option<Vec<T>>
+    where
+      T: Clone
+  {
+    if base.len() != self.base_len() {
+      return None;
+    }
+    let mut values = Vec::with_capacity(base.len());
+    let mut rest = base;
+    for edit in &self.edits {
+      match edit {
+        Edit::Keep(n) => {
+          let (kept, after) = rest.split_at(*n);
+          values.extend_from_slice(kept);
+          rest = after;
+        }
+        Edit::Delete(n) => rest = &rest[*n ..],
+        Edit::Insert(inserted) => values.extend_from_slice(inserted)
+      }
+    }
+    Some(values)
+  }
+
+  /// Returns a script which applies the changes of both `self` and `other`, which must have the same base, or an error
+  /// if they change overlapping parts of it in different ways.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the scripts have bases of different lengths.
+  pub fn merge(&self, other: &Diff<T>) -> Result<Diff<T>, MergeConflict>
+    where
+      T: PartialEq + Clone
+  {
+    let len = self.base_len();
+    assert_eq!(len, other.base_len(), "the scripts must have the same base");
+    let (ours, theirs) = (self.hunks(), other.hunks());
+    let (mut ours, mut theirs) = (ours.iter().peekable(), theirs.iter().peekable());
+
+    let mut merged = Diff { edits: Vec::new() };
+    let mut position = 0;
+    loop {
+      let hunk = match (ours.peek(), theirs.peek()) {
+        (Some(a), Some(b)) if a == b => {
+          theirs.next();
+          ours.next()
+        }
+        (Some(a), Some(b)) if a.start == b.start || (a.start < b.end && b.start < a.end) => {
+          return Err(MergeConflict { start: a.start.min(b.start), end: a.end.max(b.end) });
+        }
+        (Some(a), Some(b)) => if a.start < b.start { ours.next() } else { theirs.next() },
+        (Some(_), None) => ours.next(),
+        (None, Some(_)) => theirs.next(),
+        (None, None) => break
+      }.expect("a hunk was peeked");
+      merged.keep(hunk.start - position);
+      merged.change(&mut (hunk.end - hunk.start), &mut hunk.insert.to_vec());
+      position = hunk.end;
+    }
+    merged.keep(len - position);
+    Ok(merged)
+  }
+
+  fn hunks(&self) -> Vec<Hunk<'_, T>>
+  {
+    let mut hunks: Vec<Hunk<'_, T>> = Vec::new();
+    let mut position = 0;
+    for edit in &self.edits {
+      match edit {
+        Edit::Keep(n) => position += n,
+        Edit::Delete(n) => {
+          hunks.push(Hunk { start: position, end: position + n, insert: &[] });
+          position += n;
+        }
+        Edit::Insert(inserted) => match hunks.last_mut() {
+          Some(hunk) if hunk.end == position && hunk.insert.is_empty() => hunk.insert = inserted,
+          _ => hunks.push(Hunk { start: position, end: position, insert: inserted })
+        }
+      }
+    }
+    hunks
+  }
+
+  fn keep(&mut self, n: usize)
+  {
+    match self.edits.last_mut() {
+      _ if n == 0 => {}
+      Some(Edit::Keep(kept)) => *kept += n,
+      _ => self.edits.push(Edit::Keep(n))
+    }
+  }
+
+  // Append the deletion of `deleted` values followed by `inserted`, leaving both empty.
+  fn change(&mut self, deleted: &mut usize, inserted: &mut Vec<T>)
+  {
+    let n = std::mem::take(deleted);
+    match self.edits.as_mut_slice() {
+      _ if n == 0 => {}
+      [.., Edit::Delete(d)] | [.., Edit::Delete(d), Edit::Insert(_)] => *d += n,
+      // deleting after inserting at the same position is deleting before it
+      [.., Edit::Insert(_)] => self.edits.insert(self.edits.len() - 1, Edit::Delete(n)),
+      _ => self.edits.push(Edit::Delete(n))
+    }
+    match self.edits.last_mut() {
+      _ if inserted.is_empty() => {}
+      Some(Edit::Insert(values)) => values.append(inserted),
+      _ => self.edits.push(Edit::Insert(std::mem::take(inserted)))
+    }
+  }
+}
+
+impl MergeConflict
+{
+  /// The positions of the base which both sides change.
+  #[inline]
+  pub fn range(&self) -> Range<usize>
+  {
+    self.start .. self.end
+  }
+}
+
+impl fmt::Display for MergeConflict
+{
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+  {
+    write!(f, "both sides change the values at positions {} .. {} of the base", self.start, self.end)
+  }
+}
+
+impl std::error::Error for MergeConflict {}
@@ -1,20 +1,22 @@
 //! Access guarding with an invariant lifetime.
 
-use std::{borrow::BorrowMut, marker::PhantomData};
-use crate::{lifetime::STATIC_REGION, with_region, Region, Storable};
+use std::{any::{Any, TypeId}, borrow::BorrowMut, fmt, hash::{Hash, Hasher}, marker::PhantomData, mem::ManuallyDrop, pin::Pin};
+use crate::{lifetime::STATIC_REGION, with_region, BrandFree, CloneGenerative, Distinct, DuplicateType, GenerativeDefault, Region, SameGenerative, Storable};
 
 /// Invoke `f` with a type marker which is unique for an invariant lifetime.
 #[inline]
-pub fn with_type<U, Z>(f: impl for <'c> FnOnce(UniqueType<'c, U>) -> Z) -> Z 
+pub fn with_type<U: ?Sized, Z>(f: impl for <'c> FnOnce(UniqueType<'c, U>) -> Z) -> Z 
 {
+  #[cfg(feature = "observe")]
+  crate::observe::minted::<U>();
   with_region(|region| f(UniqueType(region, PhantomData)))
 }
 
 /// Attempt to invoke `f` with an invariant lifetime marker and a tuple of type markers that are unique for that lifetime.
 ///
-/// If any types in the tuple are duplicates, `None` is returned.
+/// If any types in the tuple are duplicates, a `DuplicateType` error is returned.
 #[inline]
-pub fn try_with_types<Types: TryGenTuple, Z>(f: impl for <'c> FnOnce(Region<'c>, Types::Tuple<'c>) -> Z) -> Option<Z> 
+pub fn try_with_types<Types: TryGenTuple, Z>(f: impl for <'c> FnOnce(Region<'c>, Types::Tuple<'c>) -> Z) -> Result<Z, DuplicateType> 
 {
   with_region(|region| Types::try_gen_tuple(region).map(|types| f(region, types)))
 }
@@ -26,77 +28,1047 @@ pub fn with_types<Types: TryGenTuple, Z>(f: impl for <'c> FnOnce(Region<'c>, Typ
   try_with_types(f).unwrap()
 }
 
+/// Invoke `f` with `value` and a type marker for its type which is unique for an invariant lifetime.
+///
+/// ```
+/// # use genz::*;
+/// fn describe<'c, T: std::fmt::Debug>(value: &T, _: UniqueType<'c, T>) -> String
+/// {
+///   format!("{:?}", value)
+/// }
+///
+/// assert_eq!("[1, 2]", with_type_for(&vec![1, 2], describe));
+/// ```
+#[inline]
+pub fn with_type_for<'v, T: ?Sized, Z>(value: &'v T, f: impl for <'c> FnOnce(&'v T, UniqueType<'c, T>) -> Z) -> Z
+{
+  with_type(|unique| f(value, unique))
+}
+
+/// Invoke `f` with `value` and a marker for its concrete type which is unique for an invariant lifetime.
+///
+/// The concrete type cannot be named statically, so the marker is erased, and must be downcast to a `UniqueType`:
+///
+/// ```
+/// # use genz::*;
+/// # use std::any::Any;
+/// fn load(plugin: &dyn Any) -> Option<u32>
+/// {
+///   with_type_of(plugin, |plugin, erased| {
+///     let _unique: UniqueType<'_, u32> = erased.downcast().ok()?;
+///     plugin.downcast_ref::<u32>().copied()
+///   })
+/// }
+///
+/// assert_eq!(Some(7), load(&7u32));
+/// assert_eq!(None, load(&"seven"));
+/// ```
+#[inline]
+pub fn with_type_of<'v, Z>(value: &'v dyn Any, f: impl for <'c> FnOnce(&'v dyn Any, ErasedType<'c>) -> Z) -> Z
+{
+  with_region(|region| f(value, ErasedType(region, value.type_id())))
+}
+
 /// A structure for storing values containing unique types.
+///
+/// A stored value is `Send` or `Sync` exactly when its stored form `Z` is, like the value itself.
 #[repr(transparent)]
-pub struct Gen<Z>(Z);
+pub struct Gen<Z>(pub(crate) Z);
+
+impl<Z: Storable> Gen<Z>
+{
+  #[inline]
+  fn from_fn(f: impl for <'c> FnOnce(Region<'c>) -> Z::Generative<'c>) -> Self 
+  {
+    Gen(f(STATIC_REGION).into())
+  }
+
+  #[inline]
+  fn try_from_fn<E>(f: impl for <'c> FnOnce(Region<'c>) -> Result<Z::Generative<'c>, E>) -> Result<Self, E> 
+  {
+    f(STATIC_REGION).map(|inner| Gen(inner.into()))
+  }
+
+  /// Created a stored value by invoking `f` with a type marker which is unique for an invariant lifetime.
+  #[inline]
+  pub fn from_type<U: ?Sized>(f: impl for <'c> FnOnce(UniqueType<'c, U>) -> Z::Generative<'c>) -> Self 
+  {
+    #[cfg(feature = "observe")]
+    crate::observe::minted::<U>();
+    Self::from_fn(|region| f(UniqueType(region, PhantomData)))
+  }
+
+  /// Attempt to create a stored value by invoking `f` with an invariant lifetime marker and a tuple of type markers that are unique for that lifetime.
+  ///
+  /// If any types in the tuple are duplicates, a `DuplicateType` error is returned.
+  /// ```
+  /// # use genz::*;
+  /// let gen = Gen::<(UniqueType<u8>, UniqueType<u16>)>::try_from_types::<(u8, u16)>(|_, (t1, t2)| {
+  ///   (t1, t2)
+  /// }).unwrap();
+  ///
+  /// // an error is returned because `u8` is repeated in the tuple
+  /// assert!(Gen::<(UniqueType<u8>, UniqueType<u8>)>::try_from_types::<(u8, u8)>(|_, (t1, t2)| {
+  ///   (t1, t2)
+  /// }).is_err())
+  /// ```
+  #[inline]
+  pub fn try_from_types<Types: TryGenTuple>(f: impl for <'c> FnOnce(Region<'c>, Types::Tuple<'c>) -> Z::Generative<'c>) -> Result<Self, DuplicateType> 
+  {
+    Self::try_from_fn(|region| Types::try_gen_tuple(region).map(|types| f(region, types)))
+  }
+
+  /// Like `try_from_types`, but it `unwrap`s for you.
+  #[inline]
+  pub fn from_types<Types: TryGenTuple>(f: impl for <'c> FnOnce(Region<'c>, Types::Tuple<'c>) -> Z::Generative<'c>) -> Self 
+  {
+    Self::try_from_types(f).unwrap()
+  }
+
+  /// Create the default stored value, built from a marker for `U` which is unique for an invariant lifetime.
+  ///
+  /// ```
+  /// # use genz::*;
+  /// struct Key;
+  /// struct Table<'c>(UniqueType<'c, Key>, Vec<u32>);
+  ///
+  /// impl Storable for Table<'static> {
+  ///   type Generative<'c> = Table<'c>;
+  /// }
+  ///
+  /// impl GenerativeDefault<Key> for Table<'static> {
+  ///   fn default_with<'c>(ty: UniqueType<'c, Key>) -> Self::Generative<'c>
+  ///   {
+  ///     Table(ty, Vec::new())
+  ///   }
+  /// }
+  ///
+  /// let table = Gen::<Table>::default_with::<Key>();
+  /// assert_eq!(0, table.with_ref(|table| table.1.len()));
+  /// ```
+  #[inline]
+  pub fn default_with<U: ?Sized>() -> Self
+    where
+      Z: GenerativeDefault<U>
+  {
+    Self::from_type(Z::default_with)
+  }
+
+  /// Invoke `f` with the moved value.
+  ///
+  /// This allows destructive operations which need ownership of the value, such as draining a collection:
+  ///
+  /// ```
+  /// # use genz::*;
+  /// struct Queue<'c>(UniqueType<'c, u8>, Vec<u32>);
+  ///
+  /// impl Storable for Queue<'static> {
+  ///   type Generative<'c> = Queue<'c>;
+  /// }
+  ///
+  /// let queue = Gen::<Queue>::from_type(|ty| Queue(ty, vec![1, 2, 3]));
+  /// let drained: Vec<_> = queue.with(|Queue(ty, items)| { ty.discard(); items.into_iter().rev().collect() });
+  ///
+  /// assert_eq!(vec![3, 2, 1], drained);
+  /// ```
+  #[inline]
+  pub fn with<R>(self, f: impl for <'c> FnOnce(Z::Generative<'c>) -> R) -> R
+  {
+    f(self.0.into())
+  }
+
+  /// Invoke `f` with a reference to the value.
+  #[inline]
+  pub fn with_ref<R>(&self, f: impl for <'c> FnOnce(&Z::Generative<'c>) -> R) -> R 
+  {
+    f(self.0.borrow())
+  }
+
+  /// Invoke `f` with a mutable reference to the value.
+  #[inline] 
+  pub fn with_mut<R>(&mut self, f: impl for <'c> FnOnce(&mut Z::Generative<'c>) -> R) -> R 
+  {
+    f(self.0.borrow_mut())
+  }
+
+  /// Invoke `f` with the region of the value and a reference to it.
+  ///
+  /// The region may be used to brand new values, without being stored in the value:
+  ///
+  /// ```
+  /// # use genz::*;
+  /// struct Ids<'c>(UniqueType<'c, u8>, Vec<u32>);
+  ///
+  /// impl Storable for Ids<'static> {
+  ///   type Generative<'c> = Ids<'c>;
+  /// }
+  ///
+  /// let mut ids = Gen::<Ids>::from_type(|ty| Ids(ty, vec![1]));
+  /// ids.with_mut_region(|_, ids| ids.1.push(2));
+  ///
+  /// assert_eq!(2, ids.with_ref_region(|region, ids| len_in(region, ids)));
+  ///
+  /// fn len_in<'c>(_: Region<'c>, ids: &Ids<'c>) -> usize { ids.1.len() }
+  /// ```
+  #[inline]
+  pub fn with_ref_region<R>(&self, f: impl for <'c> FnOnce(Region<'c>, &Z::Generative<'c>) -> R) -> R
+  {
+    f(STATIC_REGION, self.0.borrow())
+  }
+
+  /// Invoke `f` with the region of the value and a mutable reference to it.
+  #[inline]
+  pub fn with_mut_region<R>(&mut self, f: impl for <'c> FnOnce(Region<'c>, &mut Z::Generative<'c>) -> R) -> R
+  {
+    f(STATIC_REGION, self.0.borrow_mut())
+  }
+
+  /// Invoke the fallible `f` with a reference to the value, propagating its error.
+  ///
+  /// The closure may use `?` on errors of type `E`. Closures returning a `ControlFlow` may be passed to `with_ref`
+  /// directly.
+  ///
+  /// ```
+  /// # use genz::*;
+  /// # use std::num::ParseIntError;
+  /// struct Args<'c>(UniqueType<'c, u8>, Vec<&'static str>);
+  ///
+  /// impl Storable for Args<'static> {
+  ///   type Generative<'c> = Args<'c>;
+  /// }
+  ///
+  /// fn sum(args: &Gen<Args<'static>>) -> Result<u32, ParseIntError>
+  /// {
+  ///   let first = args.try_with_ref(|args| args.1[0].parse::<u32>())?;
+  ///   Ok(first + args.try_with_ref(|args| args.1[1].parse::<u32>())?)
+  /// }
+  ///
+  /// assert_eq!(Ok(3), sum(&Gen::<Args>::from_type(|ty| Args(ty, vec!["1", "2"]))));
+  /// assert!(sum(&Gen::<Args>::from_type(|ty| Args(ty, vec!["1", "two"]))).is_err());
+  /// ```
+  #[inline]
+  pub fn try_with_ref<R, E>(&self, f: impl for <'c> FnOnce(&Z::Generative<'c>) -> Result<R, E>) -> Result<R, E>
+  {
+    f(self.0.borrow())
+  }
+
+  /// Invoke the fallible `f` with a mutable reference to the value, propagating its error.
+  ///
+  /// Changes made before the error are kept.
+  #[inline]
+  pub fn try_with_mut<R, E>(&mut self, f: impl for <'c> FnOnce(&mut Z::Generative<'c>) -> Result<R, E>) -> Result<R, E>
+  {
+    f(self.0.borrow_mut())
+  }
+
+  /// Invoke the async closure `f` with a reference to the value, so the value may be used across `.await` points.
+  ///
+  /// ```
+  /// # use genz::*;
+  /// # use std::{future::Future, pin::pin, task::{Context, Poll, Waker}};
+  /// struct Cache<'c>(UniqueType<'c, u8>, Vec<u32>);
+  ///
+  /// impl Storable for Cache<'static> {
+  ///   type Generative<'c> = Cache<'c>;
+  /// }
+  ///
+  /// async fn fetch(key: u32) -> u32 { key * 10 }
+  ///
+  /// let mut cache = Gen::<Cache>::from_type(|ty| Cache(ty, Vec::new()));
+  /// let handler = async {
+  ///   cache.with_mut_async(async |cache| cache.1.push(fetch(1).await)).await;
+  ///   cache.with_ref_async(async |cache| cache.1.len()).await
+  /// };
+  ///
+  /// let mut context = Context::from_waker(Waker::noop());
+  /// assert_eq!(Poll::Ready(1), pin!(handler).poll(&mut context));
+  /// ```
+  ///
+  /// # Cancellation
+  ///
+  /// Dropping the returned future before it completes drops the future of `f` with it. Since `R` cannot name `'c`,
+  /// no branded value escapes, whether the future completes or is dropped:
+  ///
+  /// ```compile_fail
+  /// # use genz::*;
+  /// # struct Cache<'c>(UniqueType<'c, u8>, Vec<u32>);
+  /// # impl Storable for Cache<'static> { type Generative<'c> = Cache<'c>; }
+  /// let cache = Gen::<Cache>::from_type(|ty| Cache(ty, Vec::new()));
+  /// let escaped = cache.with_ref_async(async |cache| &cache.0); // fails because the marker would escape
+  /// ```
+  #[inline]
+  pub async fn with_ref_async<R>(&self, f: impl for <'c> AsyncFnOnce(&Z::Generative<'c>) -> R) -> R
+  {
+    f(self.0.borrow()).await
+  }
+
+  /// Invoke the async closure `f` with a mutable reference to the value, so the value may be used across `.await`
+  /// points.
+  ///
+  /// # Cancellation
+  ///
+  /// Dropping the returned future before it completes drops the future of `f` with it. As with `with_ref_async`, no
+  /// branded value escapes, and the value stays in the `Gen` with the changes made up to the last `.await` which was
+  /// reached:
+  ///
+  /// ```
+  /// # use genz::*;
+  /// # use std::{future::Future, pin::{pin, Pin}, task::{Context, Poll, Waker}};
+  /// struct Log<'c>(UniqueType<'c, u8>, Vec<&'static str>);
+  ///
+  /// impl Storable for Log<'static> {
+  ///   type Generative<'c> = Log<'c>;
+  /// }
+  ///
+  /// // a future which is pending the first time it is polled
+  /// struct YieldOnce(bool);
+  ///
+  /// impl Future for YieldOnce {
+  ///   type Output = ();
+  ///
+  ///   fn poll(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<()> {
+  ///     if std::mem::replace(&mut self.0, true) { Poll::Ready(()) } else { Poll::Pending }
+  ///   }
+  /// }
+  ///
+  /// let mut log = Gen::<Log>::from_type(|ty| Log(ty, Vec::new()));
+  /// let mut context = Context::from_waker(Waker::noop());
+  /// {
+  ///   let mut write = pin!(log.with_mut_async(async |log| {
+  ///     log.1.push("started");
+  ///     YieldOnce(false).await;
+  ///     log.1.push("finished");
+  ///   }));
+  ///   assert!(write.as_mut().poll(&mut context).is_pending());
+  /// } // the future is dropped while `f` is suspended
+  ///
+  /// assert_eq!(vec!["started"], log.with_ref(|log| log.1.clone()));
+  /// ```
+  #[inline]
+  pub async fn with_mut_async<R>(&mut self, f: impl for <'c> AsyncFnOnce(&mut Z::Generative<'c>) -> R) -> R
+  {
+    f(self.0.borrow_mut()).await
+  }
+
+  /// Invoke `f` with a reference to a part of the value selected by `project`, whose type does not depend on the
+  /// lifetime.
+  ///
+  /// ```
+  /// # use genz::*;
+  /// struct Table<'c>(UniqueType<'c, u8>, Vec<u32>, String);
+  ///
+  /// impl Storable for Table<'static> {
+  ///   type Generative<'c> = Table<'c>;
+  /// }
+  ///
+  /// fn rows<'a>(table: &'a Table<'_>) -> &'a Vec<u32> { &table.1 }
+  /// fn rows_mut<'a>(table: &'a mut Table<'_>) -> &'a mut Vec<u32> { &mut table.1 }
+  ///
+  /// let mut table = Gen::<Table>::from_type(|ty| Table(ty, vec![1], "ids".to_owned()));
+  /// table.with_project_mut(rows_mut, |rows| rows.push(2));
+  ///
+  /// assert_eq!(2, table.with_project(rows, Vec::len));
+  /// ```
+  #[inline]
+  pub fn with_project<P: ?Sized, R>(&self, project: impl for <'a, 'c> FnOnce(&'a Z::Generative<'c>) -> &'a P, f: impl FnOnce(&P) -> R) -> R
+  {
+    f(project(self.0.borrow()))
+  }
+
+  /// Invoke `f` with a mutable reference to a part of the value selected by `project`, whose type does not depend on
+  /// the lifetime.
+  #[inline]
+  pub fn with_project_mut<P: ?Sized, R>(&mut self, project: impl for <'a, 'c> FnOnce(&'a mut Z::Generative<'c>) -> &'a mut P, f: impl FnOnce(&mut P) -> R) -> R
+  {
+    f(project(self.0.borrow_mut()))
+  }
+
+  /// Returns a reference to a part of the value selected by `project`, whose type does not depend on the lifetime.
+  ///
+  /// Since the type of the part is named outside the closure, it cannot mention the brand, so the reference may
+  /// outlive the access:
+  ///
+  /// ```
+  /// # use genz::*;
+  /// struct Doc<'c>(UniqueType<'c, u8>, String);
+  ///
+  /// impl Storable for Doc<'static> {
+  ///   type Generative<'c> = Doc<'c>;
+  /// }
+  ///
+  /// fn title<'a>(doc: &'a Doc<'_>) -> &'a str { &doc.1 }
+  ///
+  /// let doc = Gen::<Doc>::from_type(|ty| Doc(ty, "genz".to_owned()));
+  /// let title: &str = doc.borrow_static(title);
+  ///
+  /// assert_eq!("genz", title);
+  /// ```
+  ///
+  /// ```compile_fail
+  /// # use genz::*;
+  /// # struct Doc<'c>(UniqueType<'c, u8>, String);
+  /// # impl Storable for Doc<'static> { type Generative<'c> = Doc<'c>; }
+  /// fn marker<'a, 'c>(doc: &'a Doc<'c>) -> &'a UniqueType<'c, u8> { &doc.0 }
+  ///
+  /// let doc = Gen::<Doc>::from_type(|ty| Doc(ty, String::new()));
+  /// let _ = doc.borrow_static(marker); // fails because the part mentions the brand
+  /// ```
+  #[inline]
+  pub fn borrow_static<P: ?Sized>(&self, project: impl for <'a, 'c> FnOnce(&'a Z::Generative<'c>) -> &'a P) -> &P
+  {
+    project(self.0.borrow())
+  }
+
+  /// Returns a mutable reference to a part of the value selected by `project`, whose type does not depend on the
+  /// lifetime.
+  #[inline]
+  pub fn borrow_static_mut<P: ?Sized>(&mut self, project: impl for <'a, 'c> FnOnce(&'a mut Z::Generative<'c>) -> &'a mut P) -> &mut P
+  {
+    project(self.0.borrow_mut())
+  }
+
+  /// Replace the value with `new`, and invoke `f` with the moved old value.
+  ///
+  /// The new value may be built with any constructor of `Gen`, such as `from_type`:
+  ///
+  /// ```
+  /// # use genz::*;
+  /// struct Conn<'c>(UniqueType<'c, u8>, u32);
+  ///
+  /// impl Storable for Conn<'static> {
+  ///   type Generative<'c> = Conn<'c>;
+  /// }
+  ///
+  /// let mut conn = Gen::<Conn>::from_type(|ty| Conn(ty, 1));
+  /// let closed = conn.replace(Gen::from_type(|ty| Conn(ty, 2)), |Conn(ty, id)| { ty.discard(); id });
+  ///
+  /// assert_eq!(1, closed);
+  /// assert_eq!(2, conn.with_ref(|conn| conn.1));
+  /// ```
+  #[inline]
+  pub fn replace<R>(&mut self, new: Self, f: impl for <'c> FnOnce(Z::Generative<'c>) -> R) -> R
+  {
+    std::mem::replace(self, new).with(f)
+  }
+
+  /// Replace the value with one built by `temp` for the duration of `f`, restoring the original afterwards, even if
+  /// `f` panics.
+  ///
+  /// ```
+  /// # use genz::*;
+  /// # use std::panic::{catch_unwind, AssertUnwindSafe};
+  /// struct Clock<'c>(Region<'c>, u64);
+  ///
+  /// impl Storable for Clock<'static> {
+  ///   type Generative<'c> = Clock<'c>;
+  /// }
+  ///
+  /// let mut clock = Gen::<Clock>::from_type::<()>(|ty| Clock(ty.into(), 1_700_000_000));
+  /// let faked = clock.scoped_replace(|region| Clock(region, 0), |clock| clock.with_ref(|clock| clock.1));
+  /// assert_eq!(0, faked);
+  ///
+  /// let _ = catch_unwind(AssertUnwindSafe(|| clock.scoped_replace(|region| Clock(region, 0), |_| panic!())));
+  /// assert_eq!(1_700_000_000, clock.with_ref(|clock| clock.1));
+  /// ```
+  pub fn scoped_replace<R>(&mut self, temp: impl for <'c> FnOnce(Region<'c>) -> Z::Generative<'c>, f: impl FnOnce(&mut Self) -> R) -> R
+  {
+    struct Restore<'a, Z>(&'a mut Gen<Z>, Option<Gen<Z>>);
+
+    impl<Z> Drop for Restore<'_, Z>
+    {
+      fn drop(&mut self)
+      {
+        if let Some(original) = self.1.take() {
+          *self.0 = original;
+        }
+      }
+    }
+
+    let original = std::mem::replace(self, Self::from_fn(temp));
+    let restore = Restore(self, Some(original));
+    f(restore.0)
+  }
+
+  /// Invoke `f` with a reference to each stored value in `gens`, each opened with its own invariant lifetime, and
+  /// collect the results.
+  ///
+  /// ```
+  /// # use genz::*;
+  /// struct Item<'c>(Region<'c>, u32);
+  ///
+  /// impl Storable for Item<'static> {
+  ///   type Generative<'c> = Item<'c>;
+  /// }
+  ///
+  /// let gens: Vec<_> = (1 ..= 3).map(|n| Gen::<Item>::from_type::<()>(|ty| Item(ty.into(), n))).collect();
+  ///
+  /// assert_eq!(vec![2, 4, 6], Gen::with_all(&gens, |item| item.1 * 2));
+  /// ```
+  ///
+  /// Values opened by different calls cannot be mixed:
+  ///
+  /// ```compile_fail
+  /// # use genz::*;
+  /// # struct Item<'c>(Region<'c>, u32);
+  /// # impl Storable for Item<'static> { type Generative<'c> = Item<'c>; }
+  /// # let gens: Vec<_> = (1 ..= 3).map(|n| Gen::<Item>::from_type::<()>(|ty| Item(ty.into(), n))).collect();
+  /// let mut first = None;
+  /// Gen::with_all(&gens, |item| match first {
+  ///   None => first = Some(item.0),
+  ///   Some(first) => { let _ = [first, item.0]; } // fails because each value has its own lifetime
+  /// });
+  /// ```
+  #[inline]
+  pub fn with_all<R>(gens: &[Self], mut f: impl for <'c> FnMut(&Z::Generative<'c>) -> R) -> Vec<R>
+  {
+    gens.iter().map(|gen| f(gen.0.borrow())).collect()
+  }
+
+  /// Invoke `f` with each moved stored value in `gens`, each opened with its own invariant lifetime, and collect the
+  /// results.
+  #[inline]
+  pub fn with_all_owned<R>(gens: impl IntoIterator<Item = Self>, mut f: impl for <'c> FnMut(Z::Generative<'c>) -> R) -> Vec<R>
+  {
+    gens.into_iter().map(|gen| f(gen.0.into())).collect()
+  }
+
+  /// Invoke `f` with references to two stored values, opened with distinct lifetimes, and evidence that the lifetimes
+  /// differ.
+  ///
+  /// The evidence is only about the lifetimes, so `a` and `b` may be the same value, which is then opened twice:
+  ///
+  /// ```
+  /// # use genz::*;
+  /// let a = Gen::<Region>::new_region();
+  /// Gen::with_both(&a, &a, |_, _, _distinct| ()); // accepted, although both values are `a`
+  /// ```
+  ///
+  /// ```
+  /// # use genz::*;
+  /// struct Arena<'c>(UniqueType<'c, u8>, Vec<u32>);
+  ///
+  /// impl Storable for Arena<'static> {
+  ///   type Generative<'c> = Arena<'c>;
+  /// }
+  ///
+  /// fn total<'a, 'b>(a: &Arena<'a>, b: &Arena<'b>, _: Distinct<'a, 'b>) -> u32
+  /// {
+  ///   a.1.iter().chain(&b.1).sum()
+  /// }
+  ///
+  /// let a = Gen::<Arena>::from_type(|ty| Arena(ty, vec![1, 2]));
+  /// let b = Gen::<Arena>::from_type(|ty| Arena(ty, vec![3]));
+  ///
+  /// assert_eq!(6, Gen::with_both(&a, &b, total));
+  /// ```
+  ///
+  /// The evidence requires the lifetimes to differ:
+  ///
+  /// ```compile_fail
+  /// # use genz::*;
+  /// fn same<'c>(_: Distinct<'c, 'c>) {}
+  ///
+  /// let a = Gen::<Region>::new_region();
+  /// Gen::with_both(&a, &a, |_, _, distinct| same(distinct)); // fails because the lifetimes differ
+  /// ```
+  #[inline]
+  pub fn with_both<B: Storable, R>(a: &Self, b: &Gen<B>, f: impl for <'c1, 'c2> FnOnce(&Z::Generative<'c1>, &B::Generative<'c2>, Distinct<'c1, 'c2>) -> R) -> R
+  {
+    f(a.0.borrow(), b.0.borrow(), Distinct::new())
+  }
+
+  /// Invoke `f` with mutable references to two stored values, opened with distinct lifetimes, and evidence that the
+  /// lifetimes differ.
+  ///
+  /// Branded indices of one value cannot be used with the other, so values may be moved between them safely:
+  ///
+  /// ```
+  /// # use genz::*;
+  /// struct Arena<'c>(UniqueType<'c, u8>, Vec<u32>);
+  ///
+  /// impl Storable for Arena<'static> {
+  ///   type Generative<'c> = Arena<'c>;
+  /// }
+  ///
+  /// let mut a = Gen::<Arena>::from_type(|ty| Arena(ty, vec![1, 2, 3]));
+  /// let mut b = Gen::<Arena>::from_type(|ty| Arena(ty, vec![]));
+  ///
+  /// Gen::with_both_mut(&mut a, &mut b, |a, b, _| b.1.extend(a.1.drain(2 ..)));
+  /// assert_eq!(1, b.with_ref(|b| b.1.len()));
+  /// ```
+  #[inline]
+  pub fn with_both_mut<B: Storable, R>(a: &mut Self, b: &mut Gen<B>, f: impl for <'c1, 'c2> FnOnce(&mut Z::Generative<'c1>, &mut B::Generative<'c2>, Distinct<'c1, 'c2>) -> R) -> R
+  {
+    f(a.0.borrow_mut(), b.0.borrow_mut(), Distinct::new())
+  }
+
+  /// Invoke `f` with mutable references to the stored values at `i` and `j` in `gens`, opened with distinct lifetimes,
+  /// and evidence that the lifetimes differ.
+  ///
+  /// Operations over many values, like balancing a set of arenas, may be done pairwise:
+  ///
+  /// ```
+  /// # use genz::*;
+  /// # struct Arena<'c>(UniqueType<'c, u8>, Vec<u32>);
+  /// # impl Storable for Arena<'static> { type Generative<'c> = Arena<'c>; }
+  /// let mut arenas: Vec<_> = [4, 0, 2].map(|n| Gen::<Arena>::from_type(|ty| Arena(ty, vec![0; n]))).into();
+  ///
+  /// Gen::with_pair_mut(&mut arenas, 0, 1, |a, b, _| b.1.extend(a.1.drain(2 ..)));
+  /// assert_eq!(vec![2, 2, 2], Gen::with_all(&arenas, |arena| arena.1.len()));
+  /// ```
+  ///
+  /// Panics if `i` and `j` are equal, or if either is out of bounds.
+  #[inline]
+  pub fn with_pair_mut<R>(gens: &mut [Self], i: usize, j: usize, f: impl for <'c1, 'c2> FnOnce(&mut Z::Generative<'c1>, &mut Z::Generative<'c2>, Distinct<'c1, 'c2>) -> R) -> R
+  {
+    assert!(i != j, "a stored value cannot be paired with itself");
+    let (a, b) = if i < j {
+      let (head, tail) = gens.split_at_mut(j);
+      (&mut head[i], &mut tail[0])
+    } else {
+      let (head, tail) = gens.split_at_mut(i);
+      (&mut tail[0], &mut head[j])
+    };
+    Self::with_both_mut(a, b, f)
+  }
+
+  /// Convert the value into another stored value, keeping its lifetime.
+  ///
+  /// `f` also receives the region of the value, which ties the lifetime of its result to that of its argument.
+  ///
+  /// ```
+  /// # use genz::*;
+  /// struct Named<'c>(UniqueType<'c, u8>, &'static str);
+  ///
+  /// impl Storable for Named<'static> {
+  ///   type Generative<'c> = Named<'c>;
+  /// }
+  ///
+  /// let named = Gen::<UniqueType<u8>>::from_type(|ty| ty).map::<Named>(|_, ty| Named(ty, "byte"));
+  /// assert_eq!("byte", named.with_ref(|named| named.1));
+  /// ```
+  #[inline]
+  pub fn map<W: Storable>(self, f: impl for <'c> FnOnce(Region<'c>, Z::Generative<'c>) -> W::Generative<'c>) -> Gen<W>
+  {
+    Gen(f(STATIC_REGION, self.0.into()).into())
+  }
+
+  /// Rebuild the value from the moved old one, like `map` into the same type.
+  ///
+  /// ```
+  /// # use genz::*;
+  /// struct Items<'c>(UniqueType<'c, u8>, Vec<u32>);
+  ///
+  /// impl Storable for Items<'static> {
+  ///   type Generative<'c> = Items<'c>;
+  /// }
+  ///
+  /// let items = Gen::<Items>::from_type(|ty| Items(ty, vec![1, 2, 3, 4]));
+  /// let items = items.update(|_, Items(ty, items)| Items(ty, items.into_iter().filter(|n| n % 2 == 0).collect()));
+  ///
+  /// assert_eq!(vec![2, 4], items.with_ref(|items| items.1.clone()));
+  /// ```
+  #[inline]
+  pub fn update(self, f: impl for <'c> FnOnce(Region<'c>, Z::Generative<'c>) -> Z::Generative<'c>) -> Self
+  {
+    self.map(f)
+  }
+
+  /// Rebuild the value under a fresh invariant lifetime, from the moved old one.
+  ///
+  /// Parts branded with the old lifetime must be rebuilt, with the markers of the old value converted through
+  /// `rebrand`, so nothing branded with the old lifetime survives:
+  ///
+  /// ```
+  /// # use genz::*;
+  /// struct Items<'c>(UniqueType<'c, u8>, Vec<u32>);
+  ///
+  /// impl Storable for Items<'static> {
+  ///   type Generative<'c> = Items<'c>;
+  /// }
+  ///
+  /// let items = Gen::<Items>::from_type(|ty| Items(ty, vec![1, 2]));
+  /// let items = items.rebrand(|Items(ty, items), rebrand| Items(rebrand.marker(ty), items));
+  ///
+  /// assert_eq!(vec![1, 2], items.with_ref(|items| items.1.clone()));
+  /// ```
+  ///
+  /// ```compile_fail
+  /// # use genz::*;
+  /// # struct Items<'c>(UniqueType<'c, u8>, Vec<u32>);
+  /// # impl Storable for Items<'static> { type Generative<'c> = Items<'c>; }
+  /// let items = Gen::<Items>::from_type(|ty| Items(ty, vec![1, 2]));
+  /// let items = items.rebrand(|items, _| items); // fails because the old value is branded with the old lifetime
+  /// ```
+  #[inline]
+  pub fn rebrand(self, f: impl for <'old, 'new> FnOnce(Z::Generative<'old>, Rebrand<'old, 'new>) -> Z::Generative<'new>) -> Self
+  {
+    Gen(f(self.0.into(), Rebrand(PhantomData, STATIC_REGION)).into())
+  }
+
+  /// Attempt to convert the value into another stored value, keeping its lifetime.
+  ///
+  /// If the conversion fails, `f` hands the value back along with the error, and it is returned to the caller stored
+  /// again:
+  ///
+  /// ```
+  /// # use genz::*;
+  /// struct Named<'c>(UniqueType<'c, u8>, &'static str);
+  ///
+  /// impl Storable for Named<'static> {
+  ///   type Generative<'c> = Named<'c>;
+  /// }
+  ///
+  /// type Byte = Gen<UniqueType<'static, u8>>;
+  ///
+  /// fn name(ty: Byte, name: &'static str) -> Result<Gen<Named<'static>>, (Byte, &'static str)>
+  /// {
+  ///   ty.try_map::<Named, _>(|_, ty| if name.is_empty() { Err((ty, "empty name")) } else { Ok(Named(ty, name)) })
+  /// }
+  ///
+  /// let (ty, err) = name(Gen::from_type(|ty| ty), "").map(|_| ()).unwrap_err();
+  /// assert_eq!("empty name", err);
+  /// let named = name(ty, "byte").ok().unwrap();
+  /// assert_eq!("byte", named.with_ref(|named| named.1));
+  /// ```
+  #[inline]
+  pub fn try_map<W: Storable, E>(self, f: impl for <'c> FnOnce(Region<'c>, Z::Generative<'c>) -> Result<W::Generative<'c>, (Z::Generative<'c>, E)>) -> Result<Gen<W>, (Self, E)>
+  {
+    match f(STATIC_REGION, self.0.into()) {
+      Ok(inner) => Ok(Gen(inner.into())),
+      Err((inner, err)) => Err((Gen(inner.into()), err))
+    }
+  }
+
+  /// Combine two stored values into one, so that both are opened with the same lifetime.
+  ///
+  /// ```
+  /// # use genz::*;
+  /// let a = Gen::<Region>::new_region();
+  /// let b = Gen::<Region>::new_region();
+  ///
+  /// // SAFETY: neither value contains a type marker
+  /// let both = unsafe { Gen::<(Region, Region)>::zip(a, b, |_, a, b| (a, b)) };
+  /// both.with_ref(|(a, b)| { let _: [Region; 2] = [*a, *b]; });
+  /// ```
+  ///
+  /// # Safety
+  ///
+  /// A type may be unique in each value separately, but not in both together. The values must not both contain a
+  /// type marker for the same type.
+  #[inline]
+  pub unsafe fn zip<A: Storable, B: Storable>(a: Gen<A>, b: Gen<B>, f: impl for <'c> FnOnce(Region<'c>, A::Generative<'c>, B::Generative<'c>) -> Z::Generative<'c>) -> Self
+  {
+    Gen(f(STATIC_REGION, a.0.into(), b.0.into()).into())
+  }
+
+  /// Reinterpret the value as a stored value of `W`, which has the same layout.
+  ///
+  /// The size and alignment of the types are checked at compile time, as a guard against wrong `SameGenerative` impls:
+  ///
+  /// ```compile_fail
+  /// # use genz::*;
+  /// struct Bytes<'c>(Region<'c>, [u8; 4]);
+  /// struct Word<'c>(Region<'c>, u32);
+  ///
+  /// impl Storable for Bytes<'static> { type Generative<'c> = Bytes<'c>; }
+  /// impl Storable for Word<'static> { type Generative<'c> = Word<'c>; }
+  ///
+  /// // wrong, because `u32` is aligned to 4 bytes and `[u8; 4]` to 1
+  /// unsafe impl SameGenerative<Word<'static>> for Bytes<'static> {}
+  ///
+  /// let bytes = Gen::<Bytes>::from_type::<()>(|ty| Bytes(ty.into(), [0; 4]));
+  /// let word: Gen<Word> = bytes.transmute_storable(); // fails because the alignments differ
+  /// ```
+  #[inline]
+  pub fn transmute_storable<W: Storable>(self) -> Gen<W>
+    where
+      Z: SameGenerative<W>
+  {
+    const { assert!(std::mem::size_of::<Z>() == std::mem::size_of::<W>(), "`SameGenerative` types have the same size") };
+    const {
+      assert!(std::mem::align_of::<Z>() == std::mem::align_of::<W>(), "`SameGenerative` types have the same alignment")
+    };
+    let value = ManuallyDrop::new(self.0);
+    // SAFETY: `Z: SameGenerative<W>` guarantees that a `Z` is a valid `W`, and `value` is not dropped.
+    Gen(unsafe { std::ptr::read((&*value as *const Z).cast::<W>()) })
+  }
+
+  /// Consume the stored value, returning its static form.
+  ///
+  /// To move the value out safely, use `with`, which opens it with a fresh invariant lifetime.
+  ///
+  /// # Safety
+  ///
+  /// Any number of values can be stored with the same type markers, so the static forms of two stored values may both
+  /// contain a `UniqueType<'static, T>`. The branded parts of the returned value must not be used together with those
+  /// of another value with lifetime `'static`.
+  #[inline]
+  pub unsafe fn into_inner(self) -> Z
+  {
+    self.0
+  }
+
+  /// Returns a reference to the static form of the stored value.
+  ///
+  /// # Safety
+  ///
+  /// The same as for `into_inner`.
+  #[inline]
+  pub unsafe fn as_inner_static(&self) -> &Z
+  {
+    &self.0
+  }
+
+  /// Store `raw`, the static form of a value, e.g. as returned by `into_inner`.
+  ///
+  /// `Gen<Z>` is `#[repr(transparent)]`, so it has the same layout as `Z`, and a value may be passed across an ABI
+  /// boundary by its static form.
+  ///
+  /// # Safety
+  ///
+  /// The branded parts of `raw` must not be shared with another value with lifetime `'static`, e.g. `raw` must not
+  /// contain a `UniqueType<'static, T>` which has also been returned by `into_inner` for another stored value.
+  #[inline]
+  pub unsafe fn from_raw(raw: Z) -> Self
+  {
+    Gen(raw)
+  }
+
+  /// Returns a pointer to the static form of the value.
+  ///
+  /// Dereferencing the pointer carries the same obligations as `as_inner_static`.
+  #[inline]
+  pub fn as_ptr(&self) -> *const Z
+  {
+    &self.0
+  }
+
+  /// Returns a mutable pointer to the static form of the value.
+  ///
+  /// Dereferencing the pointer carries the same obligations as `as_inner_static`.
+  #[inline]
+  pub fn as_mut_ptr(&mut self) -> *mut Z
+  {
+    &mut self.0
+  }
+
+  /// Returns an independent deep copy of the value, which is opened with a lifetime of its own.
+  #[inline]
+  pub fn snapshot(&self) -> Self
+    where
+      Z: CloneGenerative
+  {
+    Gen(Z::clone_generative(self.0.borrow()).into())
+  }
+}
+
+/// Formats the static form of the value.
+///
+/// `Gen` also implements `PartialEq`, `Eq` and `Hash` by its static form, and `Clone` by `snapshot`:
+///
+/// ```
+/// # use genz::*;
+/// # use std::{collections::HashSet, marker::PhantomData};
+/// #[derive(Debug, PartialEq, Eq, Hash)]
+/// struct Label<'c>(PhantomData<Region<'c>>, &'static str);
+///
+/// impl Storable for Label<'static> {
+///   type Generative<'c> = Label<'c>;
+/// }
+///
+/// impl CloneGenerative for Label<'static> {
+///   fn clone_generative<'c>(value: &Self::Generative<'c>) -> Self::Generative<'c>
+///   {
+///     Label(PhantomData, value.1)
+///   }
+/// }
+///
+/// let label = Gen::<Label>::from_type::<()>(|_| Label(PhantomData, "a"));
+/// assert!(format!("{:?}", label).starts_with("Gen(Label(PhantomData"));
+///
+/// let labels: HashSet<_> = [label.clone(), label].into_iter().collect();
+/// assert_eq!(1, labels.len());
+/// ```
+impl<Z: Storable> fmt::Debug for Gen<Z>
+  where
+    Z::Generative<'static>: fmt::Debug
+{
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+  {
+    f.debug_tuple("Gen").field(self.0.borrow()).finish()
+  }
+}
+
+impl<Z: CloneGenerative> Clone for Gen<Z>
+{
+  #[inline]
+  fn clone(&self) -> Self
+  {
+    self.snapshot()
+  }
+}
+
+impl<Z: Storable> PartialEq for Gen<Z>
+  where
+    Z::Generative<'static>: PartialEq
+{
+  #[inline]
+  fn eq(&self, other: &Self) -> bool
+  {
+    self.0.borrow() == other.0.borrow()
+  }
+}
+
+impl<Z: Storable> Eq for Gen<Z>
+  where
+    Z::Generative<'static>: Eq
+{}
+
+impl<Z: Storable> Hash for Gen<Z>
+  where
+    Z::Generative<'static>: Hash
+{
+  #[inline]
+  fn hash<H: Hasher>(&self, state: &mut H)
+  {
+    self.0.borrow().hash(state)
+  }
+}
+
+impl<Z: Storable> Gen<Pin<Box<Z>>>
+  where
+    Pin<Box<Z>>: From<Pin<Box<Z::Generative<'static>>>>,
+    Pin<Box<Z>>: BorrowMut<Pin<Box<Z::Generative<'static>>>>,
+    Pin<Box<Z>>: Into<Pin<Box<Z::Generative<'static>>>>
+{
+  /// Invoke `f` with a pinned mutable reference to the boxed value.
+  ///
+  /// ```
+  /// # use genz::*;
+  /// # use std::{marker::PhantomPinned, pin::Pin};
+  /// struct Node<'c>(UniqueType<'c, u8>, u32, PhantomPinned);
+  ///
+  /// impl Storable for Node<'static> {
+  ///   type Generative<'c> = Node<'c>;
+  /// }
+  ///
+  /// let mut node = Gen::<Pin<Box<Node>>>::from_type(|ty| Box::pin(Node(ty, 1, PhantomPinned)));
+  /// assert_eq!(1, node.with_pin_mut(|node: Pin<&mut Node<'_>>| node.1));
+  /// ```
+  #[inline]
+  pub fn with_pin_mut<R>(&mut self, f: impl for <'c> FnOnce(Pin<&mut Z::Generative<'c>>) -> R) -> R
+  {
+    let pinned: &mut Pin<Box<Z::Generative<'static>>> = self.0.borrow_mut();
+    f(pinned.as_mut())
+  }
+}
+
+impl<Z: Storable> Gen<Vec<Z>>
+  where
+    Vec<Z>: From<Vec<Z::Generative<'static>>>,
+    Vec<Z>: BorrowMut<Vec<Z::Generative<'static>>>,
+    Vec<Z>: Into<Vec<Z::Generative<'static>>>
+{
+  /// Invoke `f` with an iterator over references to the stored values.
+  ///
+  /// ```
+  /// # use genz::*;
+  /// struct Node<'c>(Region<'c>, u32);
+  ///
+  /// impl Storable for Node<'static> {
+  ///   type Generative<'c> = Node<'c>;
+  /// }
+  ///
+  /// let mut nodes = Gen::<Vec<Node>>::from_type::<()>(|ty| {
+  ///   let region = Region::from(ty);
+  ///   (1 ..= 3).map(|i| Node(region, i)).collect()
+  /// });
+  /// nodes.with_iter_mut(|nodes| nodes.for_each(|node| node.1 *= 2));
+  ///
+  /// assert_eq!(12, nodes.with_iter(|nodes| nodes.map(|node| node.1).sum::<u32>()));
+  /// ```
+  #[inline]
+  pub fn with_iter<R>(&self, f: impl for <'a, 'c> FnOnce(std::slice::Iter<'a, Z::Generative<'c>>) -> R) -> R
+  {
+    let values: &Vec<Z::Generative<'static>> = std::borrow::Borrow::borrow(&self.0);
+    f(values.iter())
+  }
 
-impl<Z: Storable> Gen<Z>
-{
+  /// Invoke `f` with an iterator over mutable references to the stored values.
   #[inline]
-  fn from_fn(f: impl for <'c> FnOnce(Region<'c>) -> Z::Generative<'c>) -> Self 
+  pub fn with_iter_mut<R>(&mut self, f: impl for <'a, 'c> FnOnce(std::slice::IterMut<'a, Z::Generative<'c>>) -> R) -> R
   {
-    Gen(f(STATIC_REGION).into())
+    let values: &mut Vec<Z::Generative<'static>> = self.0.borrow_mut();
+    f(values.iter_mut())
   }
+}
 
+impl<Z: GenerativeDefault> Default for Gen<Z>
+{
   #[inline]
-  fn try_from_fn(f: impl for <'c> FnOnce(Region<'c>) -> Option<Z::Generative<'c>>) -> Option<Self> 
+  fn default() -> Self
   {
-    f(STATIC_REGION).map(|inner| Gen(inner.into()))
+    Self::default_with::<()>()
   }
+}
 
-  /// Created a stored value by invoking `f` with a type marker which is unique for an invariant lifetime.
+impl<Z: BrandFree> Gen<Z>
+{
+  /// Store `value`, whose type does not depend on the lifetime.
   #[inline]
-  pub fn from_type<U>(f: impl for <'c> FnOnce(UniqueType<'c, U>) -> Z::Generative<'c>) -> Self 
+  pub fn from_value(value: Z) -> Self
   {
-    Self::from_fn(|region| f(UniqueType(region, PhantomData)))
+    Gen(value)
   }
 
-  /// Attempt to create a stored value by invoking `f` with an invariant lifetime marker and a tuple of type markers that are unique for that lifetime.
-  ///
-  /// If any types in the tuple are duplicates, `None` is returned.
-  /// ```
-  /// # use genz::*;
-  /// let gen = Gen::<(UniqueType<u8>, UniqueType<u16>)>::try_from_types::<(u8, u16)>(|_, (t1, t2)| {
-  ///   (t1, t2)
-  /// }).unwrap();
-  ///
-  /// // `None` is returned because `u8` is repeated in the tuple
-  /// assert!(Gen::<(UniqueType<u8>, UniqueType<u8>)>::try_from_types::<(u8, u8)>(|_, (t1, t2)| {
-  ///   (t1, t2)
-  /// }).is_none())
-  /// ```
+  /// Returns a reference to the value.
   #[inline]
-  pub fn try_from_types<Types: TryGenTuple>(f: impl for <'c> FnOnce(Region<'c>, Types::Tuple<'c>) -> Z::Generative<'c>) -> Option<Self> 
+  pub fn value(&self) -> &Z
   {
-    Self::try_from_fn(|region| Types::try_gen_tuple(region).map(|types| f(region, types)))
+    &self.0
   }
 
-  /// Like `try_from_types`, but it `unwrap`s for you.
+  /// Returns a mutable reference to the value.
   #[inline]
-  pub fn from_types<Types: TryGenTuple>(f: impl for <'c> FnOnce(Region<'c>, Types::Tuple<'c>) -> Z::Generative<'c>) -> Self 
+  pub fn value_mut(&mut self) -> &mut Z
   {
-    Self::try_from_types(f).unwrap()
+    &mut self.0
   }
 
-  /// Invoke `f` with the moved value.
+  /// Consume the stored value, returning the value.
   #[inline]
-  pub fn with<R>(self, f: impl for <'c> FnOnce(Z::Generative<'c>) -> R) -> R
+  pub fn into_value(self) -> Z
   {
-    f(self.0.into())
+    self.0
   }
+}
 
-  /// Invoke `f` with a reference to the value.
+impl Gen<Region<'static>>
+{
+  /// Create a stored region marker in a const context.
   #[inline]
-  pub fn with_ref<R>(&self, f: impl for <'c> FnOnce(&Z::Generative<'c>) -> R) -> R 
+  pub const fn new_region() -> Self
   {
-    f(self.0.borrow())
+    Gen(STATIC_REGION)
   }
+}
 
-  /// Invoke `f` with a mutable reference to the value.
-  #[inline] 
-  pub fn with_mut<R>(&mut self, f: impl for <'c> FnOnce(&mut Z::Generative<'c>) -> R) -> R 
+impl<T: ?Sized> Gen<UniqueType<'static, T>>
+{
+  /// Create a stored type marker in a const context, so that it can live in a `static`.
+  ///
+  /// ```
+  /// # use genz::*;
+  /// struct Config;
+  ///
+  /// static CONFIG: Gen<UniqueType<'static, Config>> = Gen::new_marker();
+  /// CONFIG.with_ref(|_: &UniqueType<'_, Config>| ());
+  /// ```
+  #[inline]
+  pub const fn new_marker() -> Self
   {
-    f(self.0.borrow_mut())
+    Gen(UniqueType(STATIC_REGION, PhantomData))
   }
 }
 
@@ -120,7 +1092,7 @@ impl<Z: Storable> Gen<Z>
 /// # use genz::*;
 /// # fn same_type<'c, T>(t1: UniqueType<'c, T>, t2: UniqueType<'c, T>) {}
 /// with_type::<u8, _>(|t1: UniqueType<'_, u8>| {
-/// 	same_type(t1, t1);
+///   same_type(t1, t1);
 /// });
 /// ```
 /// 
@@ -131,9 +1103,9 @@ impl<Z: Storable> Gen<Z>
 /// # use genz::*;
 /// # fn same_type<'c, T>(t1: UniqueType<'c, T>, t2: UniqueType<'c, T>) {}
 /// with_type::<u8, _>(|t1: UniqueType<'_, u8>| {
-/// 	with_type::<u8, _>(|t2: UniqueType<'_, u8>| {
-/// 		same_type(t1, t2); // fails because `t1` and `t2` are tagged with different lifetimes
-/// 	});	
+///   with_type::<u8, _>(|t2: UniqueType<'_, u8>| {
+///     same_type(t1, t2); // fails because `t1` and `t2` are tagged with different lifetimes
+///   });
 /// });
 /// ```
 ///
@@ -147,10 +1119,10 @@ impl<Z: Storable> Gen<Z>
 /// }
 /// 
 /// with_type::<u8, _>(|t1: UniqueType<'_, u8>| {
-/// 	with_type::<u16, _>(|t2: UniqueType<'_, u16>| {
+///   with_type::<u16, _>(|t2: UniqueType<'_, u16>| {
 ///         // fails to compile because `t1` and `t2` have different lifetimes
-/// 		different_type(t1, t2);
-/// 	});	
+///     different_type(t1, t2);
+///   });
 /// });
 /// ```
 /// 
@@ -162,25 +1134,244 @@ impl<Z: Storable> Gen<Z>
 /// # fn different_type<'c, T, U>(t1: UniqueType<'c, T>, t2: UniqueType<'c, U>) {}
 /// 
 /// let result = try_with_types::<(u8, u16), _>(|_, (t1, t2): (UniqueType<'_, u8>, UniqueType<'_, u16>)| {
-/// 	different_type(t1, t2);
+///   different_type(t1, t2);
 /// });
 /// 
-/// assert_eq!(Some(()), result);
+/// assert_eq!(Ok(()), result);
 /// ```
 /// 
-/// Notice that `try_with_types` returns an `Option`. This is because the function needs to prove that its input types 
+/// Notice that `try_with_types` returns a `Result`. This is because the function needs to prove that its input types 
 /// are distinct, but it cannot do so at compile time(pending stabilization negative traits and auto impls). 
-/// If we annotate the function call with a tuple containing duplicates, it will return `None`:
+/// If we annotate the function call with a tuple containing duplicates, it will return an error naming the repeated type:
 /// 
 /// ``` 
 /// # use genz::*;
 /// 
-/// assert_eq!(None, try_with_types::<(u8, u16, u8), _>(|_, _| panic!("should not happen")));
+/// assert_eq!("u8", try_with_types::<(u8, u16, u8), _>(|_, _| panic!("should not happen")).unwrap_err().type_name());
+/// ```
+///
+/// Because dropping a marker gives up the ability to use its type in the region, unused markers are warned about.
+/// A marker which is not needed can be dropped explicitly with `discard`:
+///
+/// ```
+/// # use genz::*;
+/// with_type::<u8, _>(|ty| ty.discard());
+/// ```
+///
+/// A marker carries no value of `T`, so it is `Send` and `Sync` whatever `T` is:
+///
+/// ```
+/// # use genz::*;
+/// # use std::rc::Rc;
+/// fn thread_safe<T: Send + Sync>(_: &T) {}
+///
+/// with_type::<Rc<u8>, _>(|ty| { thread_safe(&ty); ty.discard() });
+/// ```
+///
+/// For invariants which must stay on one thread, use a `LocalUniqueType`.
+///
+/// Markers may stand for unsized types, like `str` or trait objects:
+///
+/// ```
+/// # use genz::*;
+/// # use std::fmt::Debug;
+/// let names = with_type::<str, _>(|text| with_type::<dyn Debug, _>(|debug| [text.type_name(), debug.type_name()]));
+/// assert_eq!("str", names[0]);
+/// assert!(names[1].contains("Debug"));
 /// ```
 #[repr(transparent)]
-pub struct UniqueType<'c, T>(Region<'c>, PhantomData<T>);
+#[must_use = "dropping a type marker gives up the uniqueness of its type; use `discard` if this is intended"]
+pub struct UniqueType<'c, T: ?Sized>(Region<'c>, PhantomData<T>);
+
+// SAFETY: a marker holds no value of `T`.
+unsafe impl<T: ?Sized> Send for UniqueType<'_, T> {}
+
+// SAFETY: a marker holds no value of `T`.
+unsafe impl<T: ?Sized> Sync for UniqueType<'_, T> {}
+
+impl<T: ?Sized> UniqueType<'_, T>
+{
+  /// Explicitly drop the marker.
+  #[inline]
+  pub fn discard(self) {}
+
+  /// The name of the marked type, as given by `std::any::type_name`.
+  ///
+  /// ```
+  /// # use genz::*;
+  /// with_type::<u8, _>(|ty| {
+  ///   assert_eq!("u8", ty.type_name());
+  ///   assert_eq!("UniqueType(u8)", format!("{:?}", ty));
+  ///   ty.discard();
+  /// });
+  /// ```
+  #[inline]
+  pub fn type_name(&self) -> &'static str
+  {
+    std::any::type_name::<T>()
+  }
+}
+
+impl<T: ?Sized + 'static> UniqueType<'_, T>
+{
+  /// The `TypeId` of the marked type.
+  #[inline]
+  pub fn type_id(&self) -> TypeId
+  {
+    TypeId::of::<T>()
+  }
+}
+
+impl<T: ?Sized> fmt::Debug for UniqueType<'_, T>
+{
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+  {
+    f.debug_tuple("UniqueType").field(&format_args!("{}", self.type_name())).finish()
+  }
+}
+
+impl<'c, T: ?Sized> UniqueType<'c, T>
+{
+  // used by `TypeRegistry`, which checks the distinctness of its types
+  #[inline]
+  pub(crate) unsafe fn unchecked(region: Region<'c>) -> Self
+  {
+    UniqueType(region, PhantomData)
+  }
+
+  /// Create a marker for `T` in the region of `region`, without checking that it is unique.
+  ///
+  /// ```
+  /// # use genz::*;
+  /// struct Context;
+  ///
+  /// with_region(|region| {
+  ///   // SAFETY: no other marker for `Context` is created in this region.
+  ///   let context = unsafe { UniqueType::<Context>::new_unchecked(region) };
+  ///   context.discard();
+  /// });
+  /// ```
+  ///
+  /// # Safety
+  ///
+  /// No other `UniqueType<'c, T>` may exist while this marker, or any value built from it, is in use, including
+  /// markers created through other constructors.
+  #[cfg(feature = "unsafe_api")]
+  #[inline]
+  pub const unsafe fn new_unchecked(region: Region<'c>) -> Self
+  {
+    UniqueType(region, PhantomData)
+  }
+
+  /// Borrow the marker as evidence for `T`, like a shared reference.
+  #[inline]
+  pub fn as_type_ref(&self) -> TypeRef<'_, 'c, T>
+  {
+    TypeRef(self)
+  }
+
+  /// Borrow the marker as exclusive evidence for `T`, like a mutable reference.
+  #[inline]
+  pub fn as_type_mut(&mut self) -> TypeMut<'_, 'c, T>
+  {
+    TypeMut(self)
+  }
+}
+
+/// A shared borrow of a `UniqueType<'c, T>`, which is `Copy`, so helpers may take evidence for `T` without
+/// consuming the marker:
+///
+/// ```
+/// # use genz::*;
+/// fn check<'c>(_: TypeRef<'_, 'c, u8>, _: Region<'c>) {}
+///
+/// fn update<'c>(mut ty: TypeMut<'_, 'c, u8>, region: Region<'c>)
+/// {
+///   check(ty.as_type_ref(), region);
+///   check(ty.reborrow().as_type_ref(), region);
+/// }
+///
+/// with_type::<u8, _>(|mut ty| {
+///   let region = ty.as_type_ref().region();
+///   update(ty.as_type_mut(), region);
+///   update(ty.as_type_mut(), region);
+///   ty.discard();
+/// });
+/// ```
+pub struct TypeRef<'a, 'c, T: ?Sized>(&'a UniqueType<'c, T>);
+
+/// An exclusive borrow of a `UniqueType<'c, T>`, which may be reborrowed, like a mutable reference.
+pub struct TypeMut<'a, 'c, T: ?Sized>(&'a mut UniqueType<'c, T>);
+
+impl<'a, 'c, T: ?Sized> TypeRef<'a, 'c, T>
+{
+  /// The region of the marker.
+  #[inline]
+  pub fn region(self) -> Region<'c>
+  {
+    self.0.0
+  }
+
+  /// Returns the borrowed marker.
+  #[inline]
+  pub fn get(self) -> &'a UniqueType<'c, T>
+  {
+    self.0
+  }
+}
+
+impl<T: ?Sized> Clone for TypeRef<'_, '_, T>
+{
+  #[inline]
+  fn clone(&self) -> Self
+  {
+    *self
+  }
+}
+
+impl<T: ?Sized> Copy for TypeRef<'_, '_, T> {}
+
+impl<'c, T: ?Sized> TypeMut<'_, 'c, T>
+{
+  /// Reborrow for a shorter lifetime, keeping this borrow for later use.
+  #[inline]
+  pub fn reborrow(&mut self) -> TypeMut<'_, 'c, T>
+  {
+    TypeMut(self.0)
+  }
+
+  /// Borrow as shared evidence.
+  #[inline]
+  pub fn as_type_ref(&self) -> TypeRef<'_, 'c, T>
+  {
+    TypeRef(self.0)
+  }
+
+  /// The region of the marker.
+  #[inline]
+  pub fn region(&self) -> Region<'c>
+  {
+    self.0.0
+  }
 
-impl<'c, T> From<UniqueType<'c, T>> for Region<'c>
+  /// Returns the borrowed marker.
+  #[inline]
+  pub fn get_mut(&mut self) -> &mut UniqueType<'c, T>
+  {
+    self.0
+  }
+}
+
+impl<'a, 'c, T: ?Sized> From<TypeMut<'a, 'c, T>> for TypeRef<'a, 'c, T>
+{
+  #[inline]
+  fn from(value: TypeMut<'a, 'c, T>) -> Self
+  {
+    TypeRef(value.0)
+  }
+}
+
+impl<'c, T: ?Sized> From<UniqueType<'c, T>> for Region<'c>
 {
   #[inline]
   fn from(value: UniqueType<'c, T>) -> Self {
@@ -188,6 +1379,72 @@ impl<'c, T> From<UniqueType<'c, T>> for Region<'c>
   }
 }
 
+/// The conversion of a value from the lifetime `'old` to the fresh lifetime `'new`, passed to `Gen::rebrand`.
+pub struct Rebrand<'old, 'new>(PhantomData<Region<'old>>, Region<'new>);
+
+impl<'old, 'new> Rebrand<'old, 'new>
+{
+  /// The region of the new lifetime.
+  #[inline]
+  pub fn region(&self) -> Region<'new>
+  {
+    self.1
+  }
+
+  /// Convert a marker of the old lifetime into one of the new lifetime.
+  ///
+  /// The old marker is consumed, so the new one is as unique as the old one was.
+  #[inline]
+  pub fn marker<T: ?Sized>(&self, ty: UniqueType<'old, T>) -> UniqueType<'new, T>
+  {
+    ty.discard();
+    UniqueType(self.1, PhantomData)
+  }
+}
+
+/// A marker for a type which is unique within some region of code, but only known at runtime.
+///
+/// Like a `UniqueType`, an `ErasedType` is neither `Copy` nor `Clone`, so at most one `UniqueType<'c, _>` can be
+/// recovered from it:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// with_type_of(&0u8, |_, erased| {
+///   let t1: UniqueType<'_, u8> = erased.downcast().ok().unwrap();
+///   let t2: UniqueType<'_, u8> = erased.downcast().ok().unwrap(); // fails because `erased` was moved
+/// });
+/// ```
+#[must_use = "dropping a type marker gives up the uniqueness of its type; use `discard` if this is intended"]
+pub struct ErasedType<'c>(Region<'c>, TypeId);
+
+impl<'c> ErasedType<'c>
+{
+  /// Explicitly drop the marker.
+  #[inline]
+  pub fn discard(self) {}
+
+  /// The `TypeId` of the marked type.
+  #[inline]
+  pub fn type_id(&self) -> TypeId
+  {
+    self.1
+  }
+
+  /// Returns `true` if the marked type is `T`.
+  #[inline]
+  pub fn is<T: ?Sized + 'static>(&self) -> bool
+  {
+    self.1 == TypeId::of::<T>()
+  }
+
+  /// Convert to a `UniqueType<'c, T>` if the marked type is `T`, or return the marker otherwise.
+  #[inline]
+  pub fn downcast<T: ?Sized + 'static>(self) -> Result<UniqueType<'c, T>, Self>
+  {
+    if self.is::<T>() { Ok(UniqueType(self.0, PhantomData)) } else { Err(self) }
+  }
+}
+
 /// A trait implemented by tuples of static types.
 pub trait StaticTuple 
 {
@@ -199,7 +1456,14 @@ pub trait StaticTuple
   /// assert!(<(u8, u16)>::distinct());
   /// assert!(false == <(u8, u16, u8)>::distinct());
   /// ```
-  fn distinct() -> bool;
+  #[inline]
+  fn distinct() -> bool
+  {
+    Self::check_distinct().is_ok()
+  }
+
+  /// Returns an error describing the first repeated type in the tuple, if any.
+  fn check_distinct() -> Result<(), DuplicateType>;
 }
 
 /// A trait for creating a tuples of unique type markers.
@@ -209,7 +1473,65 @@ pub trait TryGenTuple: StaticTuple
   type Tuple<'c>;
 
   /// Returns a tuple of type markers which are unique for lifetime `'c` if every type in `Self` is distinct.
-  fn try_gen_tuple<'c>(region: Region<'c>) -> Option<Self::Tuple<'c>>;
+  fn try_gen_tuple<'c>(region: Region<'c>) -> Result<Self::Tuple<'c>, DuplicateType>;
+}
+
+/// Declares a static type which stands for `Self` when checking types for distinctness, so types which are not `'static`
+/// may receive markers from a tuple.
+///
+/// The witness cannot depend on the lifetimes of `Self`, so every instance of a type parameterized by lifetimes shares
+/// a witness, and markers for two of them are rejected as duplicates:
+///
+/// ```
+/// # use genz::*;
+/// struct Lexer<'s>(&'s str);
+/// struct Parser<'s>(&'s str);
+///
+/// impl DistinctWitness for Lexer<'_> { type Witness = Lexer<'static>; }
+/// impl DistinctWitness for Parser<'_> { type Witness = Parser<'static>; }
+///
+/// fn run<'c, 's>(_: (UniqueType<'c, Lexer<'s>>, UniqueType<'c, Parser<'s>>)) -> bool { true }
+///
+/// assert_eq!(Ok(true), try_with_witnessed_types::<(Lexer, Parser), _>(|_, types| run(types)));
+/// assert!(try_with_witnessed_types::<(Lexer, Lexer), _>(|_, _| ()).is_err());
+/// ```
+pub trait DistinctWitness
+{
+  /// The static type which stands for `Self`.
+  type Witness: ?Sized + 'static;
+}
+
+/// A trait for creating tuples of unique type markers, whose types are checked for distinctness by their witnesses.
+pub trait TryGenWitnessTuple
+{
+  /// A tuple of type markers which are unique for the lifetime `'c`.
+  type Tuple<'c>;
+
+  /// Returns a tuple of type markers which are unique for lifetime `'c` if the witness of every type in `Self` is
+  /// distinct.
+  fn try_gen_witnessed<'c>(region: Region<'c>) -> Result<Self::Tuple<'c>, DuplicateType>;
+}
+
+/// Attempt to invoke `f` with an invariant lifetime marker and a tuple of type markers that are unique for that
+/// lifetime, checking the types for distinctness by their witnesses.
+///
+/// If the witnesses of any types in the tuple are duplicates, a `DuplicateType` error is returned.
+#[inline]
+pub fn try_with_witnessed_types<Types: TryGenWitnessTuple, Z>(f: impl for <'c> FnOnce(Region<'c>, Types::Tuple<'c>) -> Z) -> Result<Z, DuplicateType>
+{
+  with_region(|region| Types::try_gen_witnessed(region).map(|types| f(region, types)))
+}
+
+fn check_ids(ids: &[TypeId], names: &[&'static str]) -> Result<(), DuplicateType>
+{
+  for i in 0 .. ids.len() {
+    for j in i + 1 .. ids.len() {
+      if ids[i] == ids[j] {
+        return Err(DuplicateType::new(names[i], i, j));
+      }
+    }
+  }
+  Ok(())
 }
 
 macro_rules! gen_tuple {
@@ -219,17 +1541,9 @@ macro_rules! gen_tuple {
           $($tt: 'static),+
       {
         #[inline]
-        fn distinct() -> bool 
+        fn check_distinct() -> Result<(), DuplicateType> 
         {
-          let ids = [$(std::any::TypeId::of::<$tt>(),)+];
-          for i in 0 .. ids.len() {
-            for j in i + 1 .. ids.len() {
-              if ids[i] == ids[j] {
-                return false;
-              }
-            }
-          }
-          true
+          check_ids(&[$(std::any::TypeId::of::<$tt>(),)+], &[$(std::any::type_name::<$tt>(),)+])
         }  
       }
 
@@ -243,6 +1557,22 @@ macro_rules! gen_tuple {
         type Generative<'c> = ($($tt::Generative<'c>,)+);
       }
 
+      impl<$($tt),+> CloneGenerative for ($($tt,)+)
+        where 
+          $($tt: CloneGenerative,)+
+          ($($tt,)+): From<($($tt::Generative<'static>,)+)>,
+          ($($tt,)+): BorrowMut<($($tt::Generative<'static>,)+)>,
+          ($($tt,)+): Into<($($tt::Generative<'static>,)+)>
+      {
+        #[inline]
+        #[allow(non_snake_case)]
+        fn clone_generative<'c>(value: &Self::Generative<'c>) -> Self::Generative<'c>
+        {
+          let ($($tt,)+) = value;
+          ($($tt::clone_generative($tt),)+)
+        }
+      }
+
       impl<$($tt),+> TryGenTuple for ($($tt,)+)
         where 
           $($tt: 'static),+
@@ -250,9 +1580,26 @@ macro_rules! gen_tuple {
         type Tuple<'c> = ($(UniqueType<'c, $tt>,)+);
 
         #[inline]
-        fn try_gen_tuple<'c>(region: Region<'c>) -> Option<Self::Tuple<'c>>
+        fn try_gen_tuple<'c>(region: Region<'c>) -> Result<Self::Tuple<'c>, DuplicateType>
+        {
+          <($($tt,)+)>::check_distinct()?;
+          #[cfg(feature = "observe")]
+          { $(crate::observe::minted::<$tt>();)+ }
+          Ok(($(UniqueType(region, PhantomData::<$tt>),)+))
+        }
+      }
+
+      impl<$($tt),+> TryGenWitnessTuple for ($($tt,)+)
+        where 
+          $($tt: DistinctWitness),+
+      {
+        type Tuple<'c> = ($(UniqueType<'c, $tt>,)+);
+
+        #[inline]
+        fn try_gen_witnessed<'c>(region: Region<'c>) -> Result<Self::Tuple<'c>, DuplicateType>
         {
-          <($($tt,)+)>::distinct().then(|| ($(UniqueType(region, PhantomData::<$tt>),)+))
+          check_ids(&[$(std::any::TypeId::of::<$tt::Witness>(),)+], &[$(std::any::type_name::<$tt>(),)+])?;
+          Ok(($(UniqueType(region, PhantomData::<$tt>),)+))
         }
       }
     };
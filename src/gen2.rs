@@ -0,0 +1,95 @@
+//! Stored values containing types which are unique for two independent lifetimes.
+
+use std::borrow::BorrowMut;
+use crate::{lifetime::STATIC_REGION, DuplicateType, Region, TryGenTuple};
+
+/// The trait of values containing types which are unique for two lifetimes, which may be stored.
+pub trait Storable2: BorrowMut<Self::Generative<'static, 'static>> + From<Self::Generative<'static, 'static>> + Into<Self::Generative<'static, 'static>>
+{
+  /// A value containing types which are unique for lifetimes `'a` and `'b`.
+  type Generative<'a, 'b>;
+}
+
+/// A structure for storing values containing types which are unique for two independent lifetimes.
+///
+/// Each access opens the value with two distinct invariant lifetimes, so parts branded by one cannot be mistaken for
+/// parts branded by the other:
+///
+/// ```
+/// # use genz::*;
+/// struct Source<'a>(Region<'a>, &'static str);
+/// struct Symbols<'b>(Region<'b>, Vec<&'static str>);
+/// struct Module<'a, 'b>(Source<'a>, Symbols<'b>);
+///
+/// impl Storable2 for Module<'static, 'static> {
+///   type Generative<'a, 'b> = Module<'a, 'b>;
+/// }
+///
+/// let mut module = Gen2::<Module>::from_regions(|a, b| Module(Source(a, "let x"), Symbols(b, Vec::new())));
+/// module.with_mut(|Module(source, symbols)| symbols.1.extend(source.1.split(' ')));
+///
+/// assert_eq!(vec!["let", "x"], module.with_ref(|module| module.1.1.clone()));
+/// ```
+///
+/// ```compile_fail
+/// # use genz::*;
+/// # struct Module<'a, 'b>(Region<'a>, Region<'b>);
+/// # impl Storable2 for Module<'static, 'static> { type Generative<'a, 'b> = Module<'a, 'b>; }
+/// fn same<'c>(_: Region<'c>, _: Region<'c>) {}
+///
+/// let module = Gen2::<Module>::from_regions(|a, b| Module(a, b));
+/// module.with_ref(|module| same(module.0, module.1)); // fails because the lifetimes are distinct
+/// ```
+#[repr(transparent)]
+pub struct Gen2<Z>(Z);
+
+impl<Z: Storable2> Gen2<Z>
+{
+  /// Create a stored value by invoking `f` with two regions of distinct invariant lifetimes.
+  #[inline]
+  pub fn from_regions(f: impl for <'a, 'b> FnOnce(Region<'a>, Region<'b>) -> Z::Generative<'a, 'b>) -> Self
+  {
+    Gen2(f(STATIC_REGION, STATIC_REGION).into())
+  }
+
+  /// Attempt to create a stored value by invoking `f` with two regions of distinct invariant lifetimes, and a tuple
+  /// of type markers that are unique for each.
+  ///
+  /// If any types in either tuple are duplicates, a `DuplicateType` error is returned. The same type may appear in
+  /// both tuples, because the lifetimes are distinct.
+  #[inline]
+  pub fn try_from_types<A: TryGenTuple, B: TryGenTuple>(f: impl for <'a, 'b> FnOnce(Region<'a>, Region<'b>, A::Tuple<'a>, B::Tuple<'b>) -> Z::Generative<'a, 'b>) -> Result<Self, DuplicateType>
+  {
+    let a = A::try_gen_tuple(STATIC_REGION)?;
+    let b = B::try_gen_tuple(STATIC_REGION)?;
+    Ok(Gen2(f(STATIC_REGION, STATIC_REGION, a, b).into()))
+  }
+
+  /// Like `try_from_types`, but it `unwrap`s for you.
+  #[inline]
+  pub fn from_types<A: TryGenTuple, B: TryGenTuple>(f: impl for <'a, 'b> FnOnce(Region<'a>, Region<'b>, A::Tuple<'a>, B::Tuple<'b>) -> Z::Generative<'a, 'b>) -> Self
+  {
+    Self::try_from_types::<A, B>(f).unwrap()
+  }
+
+  /// Invoke `f` with the moved value.
+  #[inline]
+  pub fn with<R>(self, f: impl for <'a, 'b> FnOnce(Z::Generative<'a, 'b>) -> R) -> R
+  {
+    f(self.0.into())
+  }
+
+  /// Invoke `f` with a reference to the value.
+  #[inline]
+  pub fn with_ref<R>(&self, f: impl for <'a, 'b> FnOnce(&Z::Generative<'a, 'b>) -> R) -> R
+  {
+    f(self.0.borrow())
+  }
+
+  /// Invoke `f` with a mutable reference to the value.
+  #[inline]
+  pub fn with_mut<R>(&mut self, f: impl for <'a, 'b> FnOnce(&mut Z::Generative<'a, 'b>) -> R) -> R
+  {
+    f(self.0.borrow_mut())
+  }
+}
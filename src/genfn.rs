@@ -0,0 +1,57 @@
+//! Closures over stored values.
+
+use crate::{Gen, Storable};
+
+/// A closure which captures a stored value, and opens it with a fresh invariant lifetime on every call.
+///
+/// The type of the captured value is erased, so closures over different branded state may be kept together, e.g. in a
+/// registry of event handlers:
+///
+/// ```
+/// # use genz::*;
+/// struct Counter<'c>(UniqueType<'c, u8>, u32);
+///
+/// impl Storable for Counter<'static> {
+///   type Generative<'c> = Counter<'c>;
+/// }
+///
+/// struct Log<'c>(Region<'c>, Vec<String>);
+///
+/// impl Storable for Log<'static> {
+///   type Generative<'c> = Log<'c>;
+/// }
+///
+/// let counter = Gen::<Counter>::from_type(|ty| Counter(ty, 0));
+/// let log = Gen::<Log>::from_type::<()>(|ty| Log(ty.into(), Vec::new()));
+///
+/// let mut handlers: Vec<GenFn<&str, usize>> = vec![
+///   GenFn::new(counter, |counter, _| { counter.1 += 1; counter.1 as usize }),
+///   GenFn::new(log, |log, event: &str| { log.1.push(event.to_owned()); log.1.len() })
+/// ];
+///
+/// for event in ["open", "close"] {
+///   for handler in &mut handlers {
+///     handler.call(event);
+///   }
+/// }
+///
+/// assert_eq!(vec![3, 3], handlers.iter_mut().map(|handler| handler.call("flush")).collect::<Vec<_>>());
+/// ```
+pub struct GenFn<Args, R = ()>(Box<dyn FnMut(Args) -> R>);
+
+impl<Args, R> GenFn<Args, R>
+{
+  /// Capture `state`, to be passed to `f` with a fresh invariant lifetime on every call.
+  #[inline]
+  pub fn new<Z: Storable + 'static>(mut state: Gen<Z>, mut f: impl for <'c> FnMut(&mut Z::Generative<'c>, Args) -> R + 'static) -> Self
+  {
+    GenFn(Box::new(move |args| state.with_mut(|state| f(state, args))))
+  }
+
+  /// Invoke the closure with `args`.
+  #[inline]
+  pub fn call(&mut self, args: Args) -> R
+  {
+    (self.0)(args)
+  }
+}
@@ -0,0 +1,370 @@
+//! Graph algorithms over square sparse matrices, with branded visit marks.
+
+use std::collections::VecDeque;
+use crate::{nonmax::NonMaxU32, Brand, Csr, Row};
+
+/// A set of rows of the `Csr<B, _>`, stored one bit per row.
+///
+/// Every `Row<B>` is in-bounds of the matrix the set was created for, so inserting and looking up rows needs neither
+/// hashing nor bounds checks:
+///
+/// ```
+/// # use genz::*;
+/// with_csr(3, vec![0, 0, 0, 0], vec![], Vec::<()>::new(), |m| {
+///   let mut set = BrandedBitSet::new(&m);
+///   let (a, c) = (m.row(0).unwrap(), m.row(2).unwrap());
+///
+///   assert!(set.insert(c));
+///   assert!(!set.insert(c));
+///   assert!(set.contains(c) && !set.contains(a));
+///   assert_eq!(vec![c], set.iter().collect::<Vec<_>>());
+/// });
+/// ```
+///
+/// Rows of one matrix cannot be marked in the set of another:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// with_csr(1, vec![0, 0], vec![], Vec::<()>::new(), |a| {
+///   with_csr(1, vec![0, 0], vec![], Vec::<()>::new(), |b| {
+///     BrandedBitSet::new(&b).insert(a.row(0).unwrap()); // fails because `a` and `b` have different lifetimes
+///   });
+/// });
+/// ```
+///
+/// unless the matrices are branded with a `DynRegion`, in which case marking a row of another matrix panics:
+///
+/// ```should_panic
+/// # use genz::*;
+/// let a = Csr::new(1, vec![0, 0], vec![], Vec::<()>::new()).unwrap();
+/// let b = Csr::new(1, vec![0, 0], vec![], Vec::<()>::new()).unwrap();
+/// BrandedBitSet::new(&b).insert(a.row(0).unwrap()); // panics because the row belongs to `a`
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct BrandedBitSet<B>
+{
+  brand: B,
+  len: usize,
+  words: Vec<u64>
+}
+
+impl<B: Brand> BrandedBitSet<B>
+{
+  /// Create an empty set for the rows of `matrix`.
+  #[inline]
+  pub fn new<T>(matrix: &Csr<B, T>) -> Self
+  {
+    BrandedBitSet { brand: matrix.brand(), len: matrix.nrows(), words: vec![0; matrix.nrows().div_ceil(64)] }
+  }
+
+  /// The brand of the set.
+  #[inline]
+  pub fn brand(&self) -> B
+  {
+    self.brand
+  }
+
+  /// Returns `true` if `row` is in the set.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `row` is of another matrix, which can only happen for brands checked at runtime.
+  #[inline]
+  pub fn contains(&self, row: Row<B>) -> bool
+  {
+    self.check(row);
+    let index = row.index();
+    // SAFETY: `index < nrows`, and there is a bit per row.
+    unsafe { self.words.get_unchecked(index / 64) & 1 << (index % 64) != 0 }
+  }
+
+  /// Add `row` to the set, returning `true` if it was not already in it.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `row` is of another matrix, which can only happen for brands checked at runtime.
+  #[inline]
+  pub fn insert(&mut self, row: Row<B>) -> bool
+  {
+    self.check(row);
+    let index = row.index();
+    // SAFETY: `index < nrows`, and there is a bit per row.
+    let word = unsafe { self.words.get_unchecked_mut(index / 64) };
+    let inserted = *word & 1 << (index % 64) == 0;
+    *word |= 1 << (index % 64);
+    inserted
+  }
+
+  /// Remove `row` from the set, returning `true` if it was in it.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `row` is of another matrix, which can only happen for brands checked at runtime.
+  #[inline]
+  pub fn remove(&mut self, row: Row<B>) -> bool
+  {
+    self.check(row);
+    let index = row.index();
+    // SAFETY: `index < nrows`, and there is a bit per row.
+    let word = unsafe { self.words.get_unchecked_mut(index / 64) };
+    let removed = *word & 1 << (index % 64) != 0;
+    *word &= !(1 << (index % 64));
+    removed
+  }
+
+  /// Remove every row.
+  #[inline]
+  pub fn clear(&mut self)
+  {
+    self.words.fill(0);
+  }
+
+  /// Iterate over the rows in the set, in increasing order.
+  #[inline]
+  pub fn iter(&self) -> impl Iterator<Item = Row<B>> + '_
+  {
+    let brand = self.brand;
+    // SAFETY: `index < len`, and there is a bit per row.
+    let contains = |&index: &usize| unsafe { self.words.get_unchecked(index / 64) & 1 << (index % 64) != 0 };
+    // SAFETY: `index < nrows`, which is representable because the matrix was validated.
+    (0 .. self.len).filter(contains).map(move |index| Row(unsafe { NonMaxU32::new_unchecked(index) }, brand))
+  }
+
+  #[inline]
+  fn check(&self, row: Row<B>)
+  {
+    assert!(self.brand.same_brand(row.1), "the row is of another matrix");
+  }
+}
+
+/// A square sparse matrix seen as a directed graph, with an edge from each row to the column of each of its entries.
+///
+/// Columns are rows of the same matrix, so traversals yield branded rows and mark them in a `BrandedBitSet`, without
+/// hashing or bounds checks:
+///
+/// ```
+/// # use genz::*;
+/// // 0 -> 1 -> 2 -> 0, 2 -> 3
+/// with_csr(4, vec![0, 1, 2, 4, 4], vec![1, 2, 0, 3], vec![(); 4], |m| {
+///   let graph = m.graph().unwrap();
+///   let [a, b, c, d] = [0, 1, 2, 3].map(|index| m.row(index).unwrap());
+///
+///   assert_eq!(vec![a, b, c, d], graph.dfs(a).collect::<Vec<_>>());
+///   assert_eq!(vec![c, a, d, b], graph.bfs(c).collect::<Vec<_>>());
+///   assert_eq!(None, graph.toposort());
+///   assert_eq!(vec![vec![d], vec![c, b, a]], graph.scc());
+/// });
+///
+/// // 0 -> 1, 0 -> 2, 2 -> 1
+/// with_csr(3, vec![0, 2, 2, 3], vec![1, 2, 1], vec![(); 3], |m| {
+///   let order = m.graph().unwrap().toposort().unwrap();
+///   assert_eq!(vec![0, 2, 1], order.into_iter().map(Row::index).collect::<Vec<_>>());
+/// });
+///
+/// // `None` is returned because the matrix is not square
+/// assert!(with_csr(2, vec![0, 0], vec![], Vec::<()>::new(), |m| m.graph().is_none()).unwrap());
+/// ```
+pub struct Graph<'m, B, T>(&'m Csr<B, T>);
+
+impl<B, T> Clone for Graph<'_, B, T>
+{
+  #[inline]
+  fn clone(&self) -> Self
+  {
+    *self
+  }
+}
+
+impl<B, T> Copy for Graph<'_, B, T> {}
+
+impl<B: Brand, T> Csr<B, T>
+{
+  /// Returns the matrix as a directed graph, or `None` if it is not square.
+  #[inline]
+  pub fn graph(&self) -> Option<Graph<'_, B, T>>
+  {
+    (self.nrows() == self.ncols()).then_some(Graph(self))
+  }
+}
+
+impl<'m, B: Brand, T> Graph<'m, B, T>
+{
+  /// The matrix of the graph.
+  #[inline]
+  pub fn matrix(self) -> &'m Csr<B, T>
+  {
+    self.0
+  }
+
+  /// Iterate over the targets of the edges from `row`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `row` is of another matrix, which can only happen for brands checked at runtime.
+  #[inline]
+  pub fn successors(self, row: Row<B>) -> impl Iterator<Item = Row<B>> + 'm
+  {
+    // the matrix is square, so every column is also a row
+    self.0.entries(row).map(|(col, _)| Row(col.0, col.1))
+  }
+
+  /// Iterate over the rows reachable from `start` in depth-first preorder, starting with `start`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `start` is of another matrix, which can only happen for brands checked at runtime.
+  pub fn dfs(self, start: Row<B>) -> impl Iterator<Item = Row<B>> + 'm
+  {
+    let mut visited = BrandedBitSet::new(self.0);
+    visited.insert(start);
+    let mut stack = vec![self.successors(start)];
+    let mut start = Some(start);
+    std::iter::from_fn(move || {
+      if start.is_some() {
+        return start.take();
+      }
+      while let Some(successors) = stack.last_mut() {
+        match successors.find(|&row| visited.insert(row)) {
+          Some(row) => {
+            stack.push(self.successors(row));
+            return Some(row);
+          }
+          None => {
+            stack.pop();
+          }
+        }
+      }
+      None
+    })
+  }
+
+  /// Iterate over the rows reachable from `start` in breadth-first order, starting with `start`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `start` is of another matrix, which can only happen for brands checked at runtime.
+  pub fn bfs(self, start: Row<B>) -> impl Iterator<Item = Row<B>> + 'm
+  {
+    let mut visited = BrandedBitSet::new(self.0);
+    visited.insert(start);
+    let mut queue = VecDeque::from([start]);
+    std::iter::from_fn(move || {
+      let row = queue.pop_front()?;
+      queue.extend(self.successors(row).filter(|&next| visited.insert(next)));
+      Some(row)
+    })
+  }
+
+  /// Returns every row, ordered so that each edge goes from an earlier row to a later one, or `None` if the graph has
+  /// a cycle.
+  pub fn toposort(self) -> Option<Vec<Row<B>>>
+  {
+    let (mut done, mut active) = (BrandedBitSet::new(self.0), BrandedBitSet::new(self.0));
+    let mut order = Vec::with_capacity(self.0.nrows());
+    for root in self.0.rows() {
+      if done.contains(root) {
+        continue;
+      }
+      active.insert(root);
+      let mut stack = vec![(root, self.successors(root))];
+      while let Some((row, successors)) = stack.last_mut() {
+        match successors.next() {
+          Some(next) if active.contains(next) => return None,
+          Some(next) if done.contains(next) => {}
+          Some(next) => {
+            active.insert(next);
+            stack.push((next, self.successors(next)));
+          }
+          None => {
+            let row = *row;
+            active.remove(row);
+            done.insert(row);
+            order.push(row);
+            stack.pop();
+          }
+        }
+      }
+    }
+    order.reverse();
+    Some(order)
+  }
+
+  /// Returns the strongly connected components of the graph, in reverse topological order.
+  ///
+  /// Each component lists its rows in the order in which they finished.
+  pub fn scc(self) -> Vec<Vec<Row<B>>>
+  {
+    let nrows = self.0.nrows();
+    let (mut index, mut lowlink) = (Marks(vec![0; nrows]), Marks(vec![0; nrows]));
+    let (mut visited, mut on_stack) = (BrandedBitSet::new(self.0), BrandedBitSet::new(self.0));
+    let (mut stack, mut components) = (Vec::new(), Vec::new());
+    let mut counter = 0;
+    let mut enter = |row: Row<B>, index: &mut Marks, lowlink: &mut Marks, stack: &mut Vec<Row<B>>| {
+      index.set(row, counter);
+      lowlink.set(row, counter);
+      counter += 1;
+      stack.push(row);
+    };
+
+    for root in self.0.rows() {
+      if !visited.insert(root) {
+        continue;
+      }
+      enter(root, &mut index, &mut lowlink, &mut stack);
+      on_stack.insert(root);
+      let mut calls = vec![(root, self.successors(root))];
+
+      while let Some((row, successors)) = calls.last_mut() {
+        let row = *row;
+        match successors.next() {
+          Some(next) if visited.insert(next) => {
+            enter(next, &mut index, &mut lowlink, &mut stack);
+            on_stack.insert(next);
+            calls.push((next, self.successors(next)));
+          }
+          Some(next) => if on_stack.contains(next) {
+            lowlink.set(row, lowlink.get(row).min(index.get(next)));
+          }
+          None => {
+            calls.pop();
+            if let Some(&(parent, _)) = calls.last() {
+              lowlink.set(parent, lowlink.get(parent).min(lowlink.get(row)));
+            }
+            if lowlink.get(row) == index.get(row) {
+              let mut component = Vec::new();
+              while let Some(member) = stack.pop() {
+                on_stack.remove(member);
+                component.push(member);
+                if member.0 == row.0 {
+                  break;
+                }
+              }
+              components.push(component);
+            }
+          }
+        }
+      }
+    }
+    components
+  }
+}
+
+// A number per row of the matrix of a `Graph`, which is only accessed with rows of that matrix.
+struct Marks(Vec<usize>);
+
+impl Marks
+{
+  #[inline]
+  fn get<B: Brand>(&self, row: Row<B>) -> usize
+  {
+    // SAFETY: there is a number per row, and `row < nrows`.
+    unsafe { *self.0.get_unchecked(row.index()) }
+  }
+
+  #[inline]
+  fn set<B: Brand>(&mut self, row: Row<B>, value: usize)
+  {
+    // SAFETY: there is a number per row, and `row < nrows`.
+    unsafe { *self.0.get_unchecked_mut(row.index()) = value }
+  }
+}
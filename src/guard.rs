@@ -0,0 +1,358 @@
+//! Vectors and maps which may grow, but not shrink, while branded handles to their elements exist.
+
+use std::{borrow::Borrow, collections::{hash_map::RandomState, HashMap}, hash::{BuildHasher, Hash}};
+use crate::{with_region, Brand, DynRegion, Region};
+
+/// A handle to an element of the vector of the `MutationGuard<'_, B, _>`.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct VecHandle<B>(usize, B);
+
+impl<B: Brand> VecHandle<B>
+{
+  /// The index of the element.
+  #[inline]
+  pub fn index(self) -> usize
+  {
+    self.0
+  }
+
+  /// The brand of the guard the handle belongs to.
+  #[inline]
+  pub fn brand(self) -> B
+  {
+    self.1
+  }
+}
+
+/// Exclusive access to a vector, which allows only the mutations that keep every `VecHandle<B>` in bounds.
+///
+/// Elements may be pushed, swapped and modified in place, but removing them requires giving up the guard, after which
+/// no handles of its brand can be used:
+///
+/// ```
+/// # use genz::*;
+/// let mut names = vec!["b".to_owned(), "a".to_owned()];
+///
+/// with_guard(&mut names, |mut guard| {
+///   let b = guard.handle(0).unwrap();
+///   let c = guard.push("c".to_owned());
+///   guard.get_mut(b).push('!');
+///   guard.swap(b, c);
+///
+///   assert_eq!("c", guard.get(b));
+///   assert_eq!("b!", guard.get(c));
+///   assert!(guard.handle(3).is_none());
+/// });
+///
+/// names.truncate(1);
+/// assert_eq!(vec!["c".to_owned()], names);
+/// ```
+///
+/// Handles of one guard cannot be used with another:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// let (mut a, mut b) = (vec![0], Vec::<u32>::new());
+/// with_guard(&mut a, |a| {
+///   let first = a.handle(0).unwrap();
+///   with_guard(&mut b, |b| {
+///     b.get(first); // fails because `a` and `b` have different lifetimes
+///   });
+/// });
+/// ```
+///
+/// A guard created by `MutationGuard::new` is branded with a `DynRegion` instead, and using a handle of another guard
+/// panics:
+///
+/// ```should_panic
+/// # use genz::*;
+/// let (mut a, mut b) = (vec![0], vec![0]);
+/// let (a, b) = (MutationGuard::new(&mut a), MutationGuard::new(&mut b));
+/// b.get(a.handle(0).unwrap()); // panics because the handle belongs to `a`
+/// ```
+pub struct MutationGuard<'v, B, T>
+{
+  vec: &'v mut Vec<T>,
+  brand: B
+}
+
+/// Invoke `f` with a guard over `vec`, branded with an invariant lifetime.
+#[inline]
+pub fn with_guard<'v, T, Z>(vec: &'v mut Vec<T>, f: impl for <'c> FnOnce(MutationGuard<'v, Region<'c>, T>) -> Z) -> Z
+{
+  with_region(|region| f(MutationGuard { vec, brand: region }))
+}
+
+impl<'v, T> MutationGuard<'v, DynRegion, T>
+{
+  /// Create a guard over `vec`, branded with a new `DynRegion`.
+  #[inline]
+  pub fn new(vec: &'v mut Vec<T>) -> Self
+  {
+    MutationGuard { vec, brand: DynRegion::new() }
+  }
+}
+
+impl<B: Brand, T> MutationGuard<'_, B, T>
+{
+  /// The brand of the guard.
+  #[inline]
+  pub fn brand(&self) -> B
+  {
+    self.brand
+  }
+
+  /// Returns a handle to the element at `index`, or `None` if it is out of bounds.
+  #[inline]
+  pub fn handle(&self, index: usize) -> Option<VecHandle<B>>
+  {
+    (index < self.vec.len()).then_some(VecHandle(index, self.brand))
+  }
+
+  /// Returns an iterator over handles to every element.
+  #[inline]
+  pub fn handles(&self) -> impl Iterator<Item = VecHandle<B>>
+  {
+    let brand = self.brand;
+    (0 .. self.vec.len()).map(move |index| VecHandle(index, brand))
+  }
+
+  /// Append `value`, returning its handle.
+  #[inline]
+  pub fn push(&mut self, value: T) -> VecHandle<B>
+  {
+    self.vec.push(value);
+    VecHandle(self.vec.len() - 1, self.brand)
+  }
+
+  /// Swap the elements of two handles.
+  ///
+  /// # Panics
+  ///
+  /// Panics if either handle is of another guard, which can only happen for brands checked at runtime.
+  #[inline]
+  pub fn swap(&mut self, a: VecHandle<B>, b: VecHandle<B>)
+  {
+    self.check(a);
+    self.check(b);
+    self.vec.swap(a.0, b.0)
+  }
+
+  /// Returns a reference to the element of `handle`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `handle` is of another guard, which can only happen for brands checked at runtime.
+  #[inline]
+  pub fn get(&self, handle: VecHandle<B>) -> &T
+  {
+    self.check(handle);
+    // SAFETY: `handle` was checked against this vector, which never shrinks while the guard exists.
+    unsafe { self.vec.get_unchecked(handle.0) }
+  }
+
+  /// Returns a mutable reference to the element of `handle`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `handle` is of another guard, which can only happen for brands checked at runtime.
+  #[inline]
+  pub fn get_mut(&mut self, handle: VecHandle<B>) -> &mut T
+  {
+    self.check(handle);
+    // SAFETY: `handle` was checked against this vector, which never shrinks while the guard exists.
+    unsafe { self.vec.get_unchecked_mut(handle.0) }
+  }
+
+  /// The number of elements.
+  #[inline]
+  pub fn len(&self) -> usize
+  {
+    self.vec.len()
+  }
+
+  /// Returns `true` if the vector has no elements.
+  #[inline]
+  pub fn is_empty(&self) -> bool
+  {
+    self.vec.is_empty()
+  }
+
+  #[inline]
+  fn check(&self, handle: VecHandle<B>)
+  {
+    assert!(self.brand.same_brand(handle.1), "the handle is of another guard");
+  }
+}
+
+/// A handle to an entry of the map of the `MapGuard<'_, B, _, _, _>`, which holds a copy of its key.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct MapHandle<B, K>(K, B);
+
+impl<B: Brand, K> MapHandle<B, K>
+{
+  /// The key of the entry.
+  #[inline]
+  pub fn key(&self) -> &K
+  {
+    &self.0
+  }
+
+  /// The brand of the guard the handle belongs to.
+  #[inline]
+  pub fn brand(&self) -> B
+  {
+    self.1
+  }
+}
+
+/// Exclusive access to a map, which allows only the mutations that keep an entry for every `MapHandle<B, _>`.
+///
+/// Entries may be inserted and their values modified, but removing them requires giving up the guard, after which no
+/// handles of its brand can be used:
+///
+/// ```
+/// # use genz::*;
+/// # use std::collections::HashMap;
+/// let mut stock = HashMap::from([("apples", 3), ("pears", 0)]);
+///
+/// with_map_guard(&mut stock, |mut guard| {
+///   let apples = guard.handle("apples").unwrap();
+///   let plums = guard.insert("plums", 5);
+///   *guard.get_mut(&apples) += 1;
+///
+///   assert_eq!(9, *guard.get(&apples) + *guard.get(&plums));
+///   assert!(guard.handle("cherries").is_none());
+/// });
+///
+/// stock.retain(|_, count| *count > 0);
+/// assert_eq!(2, stock.len());
+/// ```
+///
+/// Handles of one guard cannot be used with another:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// # use std::collections::HashMap;
+/// let (mut a, mut b) = (HashMap::from([(0, 0)]), HashMap::<u32, u32>::new());
+/// with_map_guard(&mut a, |a| {
+///   let first = a.handle(&0).unwrap();
+///   with_map_guard(&mut b, |b| {
+///     b.get(&first); // fails because `a` and `b` have different lifetimes
+///   });
+/// });
+/// ```
+///
+/// A guard created by `MapGuard::new` is branded with a `DynRegion` instead, and using a handle of another guard
+/// panics:
+///
+/// ```should_panic
+/// # use genz::*;
+/// # use std::collections::HashMap;
+/// let (mut a, mut b) = (HashMap::from([(0, 0)]), HashMap::from([(0, 0)]));
+/// let (a, b) = (MapGuard::new(&mut a), MapGuard::new(&mut b));
+/// b.get(&a.handle(&0).unwrap()); // panics because the handle belongs to `a`
+/// ```
+pub struct MapGuard<'m, B, K, V, S = RandomState>
+{
+  map: &'m mut HashMap<K, V, S>,
+  brand: B
+}
+
+/// Invoke `f` with a guard over `map`, branded with an invariant lifetime.
+#[inline]
+pub fn with_map_guard<'m, K, V, S, Z>(map: &'m mut HashMap<K, V, S>, f: impl for <'c> FnOnce(MapGuard<'m, Region<'c>, K, V, S>) -> Z) -> Z
+{
+  with_region(|region| f(MapGuard { map, brand: region }))
+}
+
+impl<'m, K, V, S> MapGuard<'m, DynRegion, K, V, S>
+{
+  /// Create a guard over `map`, branded with a new `DynRegion`.
+  #[inline]
+  pub fn new(map: &'m mut HashMap<K, V, S>) -> Self
+  {
+    MapGuard { map, brand: DynRegion::new() }
+  }
+}
+
+impl<B: Brand, K: Eq + Hash + Clone, V, S: BuildHasher> MapGuard<'_, B, K, V, S>
+{
+  /// The brand of the guard.
+  #[inline]
+  pub fn brand(&self) -> B
+  {
+    self.brand
+  }
+
+  /// Returns a handle to the entry of `key`, or `None` if there is no such entry.
+  #[inline]
+  pub fn handle<Q: Eq + Hash + ?Sized>(&self, key: &Q) -> Option<MapHandle<B, K>>
+    where
+      K: Borrow<Q>
+  {
+    self.map.get_key_value(key).map(|(key, _)| MapHandle(key.clone(), self.brand))
+  }
+
+  /// Returns an iterator over handles to every entry, in the order of the map.
+  #[inline]
+  pub fn handles(&self) -> impl Iterator<Item = MapHandle<B, K>> + '_
+  {
+    self.map.keys().map(|key| MapHandle(key.clone(), self.brand))
+  }
+
+  /// Insert `value` for `key`, replacing the value of an existing entry, and return the handle of the entry.
+  #[inline]
+  pub fn insert(&mut self, key: K, value: V) -> MapHandle<B, K>
+  {
+    let handle = MapHandle(key.clone(), self.brand);
+    self.map.insert(key, value);
+    handle
+  }
+
+  /// Returns a reference to the value of `handle`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `handle` is of another guard, which can only happen for brands checked at runtime, or if the `Hash` and
+  /// `Eq` implementations of the key no longer find its entry.
+  #[inline]
+  pub fn get(&self, handle: &MapHandle<B, K>) -> &V
+  {
+    self.check(handle);
+    self.map.get(&handle.0).expect("entries are not removed while the map is guarded")
+  }
+
+  /// Returns a mutable reference to the value of `handle`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `handle` is of another guard, which can only happen for brands checked at runtime, or if the `Hash` and
+  /// `Eq` implementations of the key no longer find its entry.
+  #[inline]
+  pub fn get_mut(&mut self, handle: &MapHandle<B, K>) -> &mut V
+  {
+    self.check(handle);
+    self.map.get_mut(&handle.0).expect("entries are not removed while the map is guarded")
+  }
+
+  /// The number of entries.
+  #[inline]
+  pub fn len(&self) -> usize
+  {
+    self.map.len()
+  }
+
+  /// Returns `true` if the map has no entries.
+  #[inline]
+  pub fn is_empty(&self) -> bool
+  {
+    self.map.is_empty()
+  }
+
+  #[inline]
+  fn check(&self, handle: &MapHandle<B, K>)
+  {
+    assert!(self.brand.same_brand(handle.1), "the handle is of another guard");
+  }
+}
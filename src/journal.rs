@@ -0,0 +1,206 @@
+//! Undo logs of the mutations of token cells.
+
+use crate::{DynRegion, Region, Token, TokenCell};
+
+/// A token which records the values it overwrites in cells borrowed for `'a`, so that mutations can be undone and
+/// redone.
+///
+/// The journal owns the token of region `'c`, so cells of the region are only mutated through it while it lives:
+///
+/// ```
+/// # use genz::*;
+/// with_token(|token| {
+///   let region = token.region();
+///   let (title, words) = (TokenCell::new(String::from("draft"), region), TokenCell::new(0, region));
+///   let mut journal = Journal::new(token);
+///
+///   journal.set(&title, String::from("essay"));
+///   let saved = journal.snapshot();
+///   journal.update(&words, |words| *words += 120);
+///   journal.update(&words, |words| *words += 80);
+///
+///   assert!(journal.undo());
+///   assert_eq!(120, *journal.get(&words));
+///   assert!(journal.redo());
+///   assert_eq!(200, *journal.get(&words));
+///
+///   assert!(journal.restore(saved));
+///   assert_eq!(("essay", 0), (journal.get(&title).as_str(), *journal.get(&words)));
+///
+///   // undoing everything restores the values the cells had before the journal
+///   while journal.undo() {}
+///   let token = journal.into_token();
+///   assert_eq!("draft", title.borrow(&token));
+/// });
+/// ```
+pub struct Journal<'a, 'c>
+{
+  token: Token<'c>,
+  changes: Vec<(u64, Box<dyn Change<'c> + 'a>)>,
+  applied: usize,
+  id: DynRegion,
+  next: u64
+}
+
+/// A point in the history of the `Journal<'_, 'c>` it was taken from, which `restore` returns to.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Checkpoint<'c>
+{
+  journal: DynRegion,
+  applied: usize,
+  last: u64,
+  region: Region<'c>
+}
+
+// A recorded mutation, which is undone and redone by swapping the recorded value with the value of the cell.
+trait Change<'c>
+{
+  fn swap(&mut self, token: &mut Token<'c>);
+}
+
+// The value overwritten in `cell`, or the value to write into it when redoing.
+struct Write<'a, 'c, T>
+{
+  cell: &'a TokenCell<'c, T>,
+  value: T
+}
+
+impl<'c, T> Change<'c> for Write<'_, 'c, T>
+{
+  #[inline]
+  fn swap(&mut self, token: &mut Token<'c>)
+  {
+    std::mem::swap(self.cell.borrow_mut(token), &mut self.value)
+  }
+}
+
+impl<'a, 'c> Journal<'a, 'c>
+{
+  /// Create a journal with an empty history, which owns `token`.
+  #[inline]
+  pub fn new(token: Token<'c>) -> Self
+  {
+    Journal { token, changes: Vec::new(), applied: 0, id: DynRegion::new(), next: 0 }
+  }
+
+  /// The token, for reading cells.
+  #[inline]
+  pub fn token(&self) -> &Token<'c>
+  {
+    &self.token
+  }
+
+  /// Returns a shared reference to the contents of `cell`.
+  #[inline]
+  pub fn get<'r, T: ?Sized>(&'r self, cell: &'r TokenCell<'c, T>) -> &'r T
+  {
+    cell.borrow(&self.token)
+  }
+
+  /// Write `value` into `cell`, recording the overwritten value.
+  ///
+  /// Changes which were undone can no longer be redone.
+  pub fn set<T: 'a>(&mut self, cell: &'a TokenCell<'c, T>, value: T)
+  {
+    let value = std::mem::replace(cell.borrow_mut(&mut self.token), value);
+    self.record(Box::new(Write { cell, value }));
+  }
+
+  /// Invoke `f` with a mutable reference to the contents of `cell`, recording a clone of them beforehand.
+  ///
+  /// Changes which were undone can no longer be redone.
+  pub fn update<T: Clone + 'a, R>(&mut self, cell: &'a TokenCell<'c, T>, f: impl FnOnce(&mut T) -> R) -> R
+  {
+    let contents = cell.borrow_mut(&mut self.token);
+    let value = contents.clone();
+    let result = f(contents);
+    self.record(Box::new(Write { cell, value }));
+    result
+  }
+
+  /// Undo the latest change which was not undone, returning `false` if there is none.
+  pub fn undo(&mut self) -> bool
+  {
+    if self.applied == 0 {
+      return false;
+    }
+    self.applied -= 1;
+    self.changes[self.applied].1.swap(&mut self.token);
+    true
+  }
+
+  /// Redo the earliest change which was undone, returning `false` if there is none.
+  pub fn redo(&mut self) -> bool
+  {
+    if self.applied == self.changes.len() {
+      return false;
+    }
+    self.changes[self.applied].1.swap(&mut self.token);
+    self.applied += 1;
+    true
+  }
+
+  /// Returns the current point in the history.
+  #[inline]
+  pub fn snapshot(&self) -> Checkpoint<'c>
+  {
+    Checkpoint { journal: self.id, applied: self.applied, last: self.last(self.applied), region: self.token.region() }
+  }
+
+  /// Undo or redo changes until the cells have the values they had at `checkpoint`.
+  ///
+  /// Returns `false`, and changes nothing, if `checkpoint` was taken from another journal, or is no longer in the
+  /// history because changes were recorded after undoing past it:
+  ///
+  /// ```
+  /// # use genz::*;
+  /// with_token(|token| {
+  ///   let cell = TokenCell::new(1, token.region());
+  ///   let mut journal = Journal::new(token);
+  ///
+  ///   journal.set(&cell, 2);
+  ///   let two = journal.snapshot();
+  ///   journal.undo();
+  ///   journal.set(&cell, 3); // discards the change to 2
+  ///
+  ///   assert!(!journal.restore(two));
+  ///   assert_eq!(3, *journal.get(&cell));
+  /// });
+  /// ```
+  pub fn restore(&mut self, checkpoint: Checkpoint<'c>) -> bool
+  {
+    let Checkpoint { journal, applied, last, region: _ } = checkpoint;
+    if journal != self.id || applied > self.changes.len() || self.last(applied) != last {
+      return false;
+    }
+    while self.applied > applied {
+      self.undo();
+    }
+    while self.applied < applied {
+      self.redo();
+    }
+    true
+  }
+
+  /// Returns the token, discarding the history and keeping the current values of the cells.
+  #[inline]
+  pub fn into_token(self) -> Token<'c>
+  {
+    self.token
+  }
+
+  // The id of the last of the first `applied` changes, or 0 if there are none.
+  fn last(&self, applied: usize) -> u64
+  {
+    applied.checked_sub(1).map_or(0, |index| self.changes[index].0)
+  }
+
+  // Record `change`, discarding the changes which were undone.
+  fn record(&mut self, change: Box<dyn Change<'c> + 'a>)
+  {
+    self.changes.truncate(self.applied);
+    self.next += 1;
+    self.changes.push((self.next, change));
+    self.applied += 1;
+  }
+}
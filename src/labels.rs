@@ -0,0 +1,119 @@
+//! Regions labeled for diagnostics.
+
+use std::fmt;
+#[cfg(feature = "debug-labels")]
+use std::{any::Any, panic::{self, AssertUnwindSafe}};
+use crate::{with_region, Region};
+
+/// An invariant lifetime marker with a label, which is kept when the `debug-labels` feature is enabled.
+///
+/// The label shows in `Debug` output, and in the messages of panics which unwind out of `with_labeled_region`.
+/// Without the feature, the label is discarded, and the marker is as small as a `Region`:
+///
+/// ```
+/// # use genz::*;
+/// with_labeled_region("db", |region| {
+///   if let Some(label) = region.label() {
+///     assert_eq!("db", label);
+///     assert_eq!("LabeledRegion(\"db\")", format!("{:?}", region));
+///   }
+///
+///   let _plain: Region = region.into();
+/// });
+/// ```
+#[derive(Copy, Clone)]
+pub struct LabeledRegion<'c>
+{
+  region: Region<'c>,
+  #[cfg(feature = "debug-labels")]
+  label: &'static str
+}
+
+/// Invoke `f` with an invariant lifetime marker labeled with `label`.
+///
+/// When the `debug-labels` feature is enabled, a panic with a string message which unwinds out of `f` is resumed with
+/// the label appended to its message, so the label is part of the payload seen by whoever catches or joins it:
+///
+/// ```
+/// # use genz::*;
+/// # use std::panic::catch_unwind;
+/// let payload = catch_unwind(|| with_labeled_region("db", |_| panic!("connection lost"))).unwrap_err();
+/// let message = payload.downcast_ref::<String>().map(String::as_str).or(payload.downcast_ref::<&str>().copied());
+///
+/// if with_labeled_region("db", |region| region.label()).is_some() {
+///   assert_eq!(Some("connection lost\n  in region `db`"), message);
+/// } else {
+///   assert_eq!(Some("connection lost"), message);
+/// }
+/// ```
+///
+/// The panic hook runs before the payload is caught, so the message it prints does not include the label. Payloads
+/// which are not strings are resumed unchanged.
+#[inline]
+pub fn with_labeled_region<Z>(label: &'static str, f: impl for <'c> FnOnce(LabeledRegion<'c>) -> Z) -> Z
+{
+  #[cfg(feature = "debug-labels")]
+  {
+    // the unwind is resumed right away, so no state broken by the panic can be observed
+    let result = panic::catch_unwind(AssertUnwindSafe(|| with_region(|region| f(LabeledRegion { region, label }))));
+    result.unwrap_or_else(|payload| panic::resume_unwind(labeled(payload, label)))
+  }
+  #[cfg(not(feature = "debug-labels"))]
+  {
+    let _ = label;
+    with_region(|region| f(LabeledRegion { region }))
+  }
+}
+
+#[cfg(feature = "debug-labels")]
+fn labeled(payload: Box<dyn Any + Send>, label: &str) -> Box<dyn Any + Send>
+{
+  let message = match payload.downcast::<String>() {
+    Ok(message) => *message,
+    Err(payload) => match payload.downcast::<&'static str>() {
+      Ok(message) => message.to_string(),
+      Err(payload) => return payload
+    }
+  };
+  Box::new(format!("{message}\n  in region `{label}`"))
+}
+
+impl<'c> LabeledRegion<'c>
+{
+  /// The region.
+  #[inline]
+  pub fn region(self) -> Region<'c>
+  {
+    self.region
+  }
+
+  /// The label, or `None` if the `debug-labels` feature is disabled.
+  #[inline]
+  pub fn label(self) -> Option<&'static str>
+  {
+    #[cfg(feature = "debug-labels")]
+    return Some(self.label);
+    #[cfg(not(feature = "debug-labels"))]
+    None
+  }
+}
+
+impl<'c> From<LabeledRegion<'c>> for Region<'c>
+{
+  #[inline]
+  fn from(region: LabeledRegion<'c>) -> Self
+  {
+    region.region
+  }
+}
+
+impl fmt::Debug for LabeledRegion<'_>
+{
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+  {
+    match self.label() {
+      Some(label) => f.debug_tuple("LabeledRegion").field(&label).finish(),
+      None => f.write_str("LabeledRegion")
+    }
+  }
+}
@@ -0,0 +1,138 @@
+//! Iterators lending short-lived views.
+
+use crate::Scope;
+
+mod sealed
+{
+  pub trait Sealed: Sized {}
+
+  pub struct Bounds<T>(T);
+
+  impl<T> Sealed for Bounds<T> {}
+}
+
+/// The type of the items a lending iterator lends for lifetime `'a`.
+///
+/// The defaulted parameter implies `Self: 'a`, so that `for <'a> LendItem<'a>` only ranges over lifetimes for which
+/// `Self` is valid.
+pub trait LendItem<'a, ImplicitBounds: sealed::Sealed = sealed::Bounds<&'a Self>>
+{
+  /// The type of the lent items.
+  type Item;
+}
+
+/// The type of the items `L` lends for lifetime `'a`.
+pub type Lent<'a, L> = <L as LendItem<'a>>::Item;
+
+/// An iterator whose items borrow from the iterator itself, so at most one item is live at a time.
+pub trait LendingIterator: for <'a> LendItem<'a>
+{
+  /// Advance the iterator and return the next item.
+  fn next(&mut self) -> Option<Lent<'_, Self>>;
+
+  /// Invoke `f` with every remaining item.
+  #[inline]
+  fn for_each(mut self, mut f: impl FnMut(Lent<'_, Self>))
+    where
+      Self: Sized
+  {
+    while let Some(item) = self.next() {
+      f(item);
+    }
+  }
+
+  /// Count the remaining items.
+  #[inline]
+  fn count(self) -> usize
+    where
+      Self: Sized
+  {
+    let mut count = 0;
+    self.for_each(|_| count += 1);
+    count
+  }
+
+  /// Turn the items into owned values with `f`, producing an ordinary iterator.
+  #[inline]
+  fn map<B, F>(self, f: F) -> LendMap<Self, F>
+    where
+      Self: Sized,
+      F: FnMut(Lent<'_, Self>) -> B
+  {
+    LendMap { iter: self, f }
+  }
+}
+
+/// A lending iterator over the items of `I`, each of which is lent as a `&mut` for no longer than `'c`.
+pub struct Lend<'c, I: Iterator>
+{
+  iter: I,
+  current: Option<I::Item>,
+  _scope: Scope<'c>
+}
+
+/// Lend the items of `iter` one at a time, for no longer than the `scope`.
+///
+/// ```
+/// # use genz::*;
+/// let lengths = with_scope(|scope| {
+///   let mut words = lend_iter(scope, ["a", "bb", "ccc"].map(String::from));
+///   words.next().unwrap().push('!');
+///   words.map(|word| word.len()).collect::<Vec<_>>()
+/// });
+///
+/// assert_eq!(vec![2, 3], lengths);
+/// ```
+///
+/// Only one item is live at a time:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// with_scope(|scope| {
+///   let mut words = lend_iter(scope, vec![String::new(), String::new()]);
+///   let a = words.next().unwrap();
+///   let b = words.next().unwrap(); // fails because `a` is still borrowed
+///   a.push_str(b);
+/// });
+/// ```
+#[inline]
+pub fn lend_iter<'c, I: IntoIterator>(scope: Scope<'c>, iter: I) -> Lend<'c, I::IntoIter>
+{
+  Lend { iter: iter.into_iter(), current: None, _scope: scope }
+}
+
+impl<'a, I: Iterator> LendItem<'a> for Lend<'_, I>
+{
+  type Item = &'a mut I::Item;
+}
+
+impl<I: Iterator> LendingIterator for Lend<'_, I>
+{
+  #[inline]
+  fn next(&mut self) -> Option<Lent<'_, Self>>
+  {
+    self.current = self.iter.next();
+    self.current.as_mut()
+  }
+}
+
+/// An iterator which maps the items of a lending iterator into owned values.
+pub struct LendMap<L, F>
+{
+  iter: L,
+  f: F
+}
+
+impl<L, F, B> Iterator for LendMap<L, F>
+  where
+    L: LendingIterator,
+    F: FnMut(Lent<'_, L>) -> B
+{
+  type Item = B;
+
+  #[inline]
+  fn next(&mut self) -> Option<B>
+  {
+    self.iter.next().map(&mut self.f)
+  }
+}
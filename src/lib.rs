@@ -12,11 +12,158 @@
 
 mod lifetime;
 #[doc(inline)]
-pub use lifetime::{Scope, Region, with_region, with_scope};
+pub use lifetime::{Scope, CoScope, Region, ChildOf, Outlives, Distinct, STATIC_SCOPE, STATIC_REGION, with_region, with_scope, with_coscope};
 
 mod storable;
-pub use storable::Storable;
+pub use storable::{Storable, CloneGenerative, SameGenerative, BrandFree, GenerativeDefault};
 
 mod gen;
 #[doc(inline)]
-pub use gen::{Gen, UniqueType, TryGenTuple, StaticTuple, with_type, try_with_types, with_types};
\ No newline at end of file
+pub use gen::{Gen, UniqueType, TypeRef, TypeMut, ErasedType, Rebrand, TryGenTuple, StaticTuple, DistinctWitness, TryGenWitnessTuple, with_type, with_type_for, with_type_of, try_with_types, with_types, try_with_witnessed_types};
+
+mod registry;
+pub use registry::TypedGenRegistry;
+
+mod nonmax;
+
+mod fenwick;
+pub use fenwick::{Fenwick, FenwickIndex, with_fenwick};
+
+mod csr;
+pub use csr::{Csr, Row, Col, with_csr};
+
+mod graph;
+pub use graph::{BrandedBitSet, Graph};
+
+mod ast;
+pub use ast::{AstArena, Kind, NodeId};
+
+mod dma;
+pub use dma::{DmaChannel, DmaToken, DmaTransfer, DmaDone, with_dma};
+
+mod span;
+pub use span::{Source, Span, with_source};
+
+mod open;
+pub use open::OpenAll;
+
+mod dynamic;
+pub use dynamic::{DynRegion, DynUnique, TryGenDynTuple, dyn_type, try_dyn_types};
+
+mod brand;
+pub use brand::Brand;
+
+mod self_ref;
+pub use self_ref::SelfRef;
+
+mod bimap;
+pub use bimap::{BiMap, BiMapId, with_bimap};
+
+mod lend;
+pub use lend::{LendingIterator, LendItem, Lent, Lend, LendMap, lend_iter};
+
+mod memo;
+pub use memo::{DenseId, Memo};
+
+mod cache;
+pub use cache::RegionCache;
+
+mod error;
+pub use error::DuplicateType;
+
+mod spsc;
+pub use spsc::{Queue, Producer, Consumer};
+
+mod pool;
+pub use pool::{Pool, Lease, with_pool};
+
+mod arena;
+pub use arena::{Arena, Key, KeyMap, Trace, with_arena};
+
+mod bytes;
+pub use bytes::{ByteCursor, ByteRange, with_bytes};
+
+mod slice;
+pub use slice::{BrandedSlice, Window, Windows, Disjoint, DisjointPairs, with_branded_slice};
+
+mod vec;
+pub use vec::{BrandedVec, BrandedIndex, Cursor, Unedited, Edited, Remap, with_branded_vec};
+
+mod frozen;
+pub use frozen::{FrozenVec, Diff, Edit, MergeConflict, diff, merge, with_frozen_vec};
+
+mod validated;
+pub use validated::{Validator, Validated};
+
+#[cfg(feature = "observe")]
+mod observe;
+#[cfg(feature = "observe")]
+pub use observe::{RegionObserver, set_observer};
+
+mod config;
+pub use config::{ConfigStack, Setting};
+
+mod guard;
+pub use guard::{MutationGuard, VecHandle, MapGuard, MapHandle, with_guard, with_map_guard};
+
+mod slot;
+pub use slot::GenSlot;
+
+mod limit;
+pub use limit::{RateLimiter, Permit, Limited, with_limiter};
+
+mod threads;
+pub use threads::{ThreadPool, JobHandle};
+
+mod machine;
+
+mod envelope;
+pub use envelope::{Export, Envelope};
+
+mod shared;
+pub use shared::{GenRc, GenArc};
+
+mod rcu;
+pub use rcu::{Rcu, Reader, Snapshot};
+
+mod gen2;
+pub use gen2::{Storable2, Gen2};
+
+mod genfn;
+pub use genfn::GenFn;
+
+mod builder;
+pub use builder::{Builder, Build, Fields, Has, Here, There};
+
+mod di;
+pub use di::{Injector, Dependencies, Resolver};
+
+mod markers;
+pub use markers::{ConstTypes, TypeAt, IndexOf, MintMarkers, with_const_types};
+
+mod erased;
+pub use erased::GenAny;
+
+mod cross;
+pub use cross::{Container, Ref};
+
+mod claim;
+pub use claim::{TypeRegistry, with_type_registry};
+
+mod make;
+#[doc(hidden)]
+pub use make::__make_region;
+
+mod local;
+pub use local::{LocalRegion, LocalUniqueType, with_local_region};
+
+mod labels;
+pub use labels::{LabeledRegion, with_labeled_region};
+
+mod cell;
+pub use cell::{Token, TokenCell, with_token};
+
+mod journal;
+pub use journal::{Journal, Checkpoint};
+
+pub mod test;
@@ -1,6 +1,6 @@
 //! Anonymous lifetime markers.
 
-use std::marker::PhantomData;
+use std::{fmt, marker::PhantomData};
 
 /// A covariant lifetime marker.
 ///
@@ -32,6 +32,59 @@ use std::marker::PhantomData;
 #[derive(Copy, Clone)]
 pub struct Scope<'c>(PhantomData<&'c ()>);
 
+impl<'c> Scope<'c>
+{
+  /// Shorten the lifetime of the marker.
+  ///
+  /// This is what covariance allows implicitly, spelled out for when the target lifetime must be named:
+  ///
+  /// ```
+  /// # use genz::*;
+  /// fn inner<'o: 's, 's>(outer: Scope<'o>, _: &'s ()) -> Scope<'s>
+  /// {
+  ///   outer.shorten()
+  /// }
+  ///
+  /// let _ = inner(STATIC_SCOPE, &());
+  /// ```
+  #[inline]
+  pub fn shorten<'s>(self) -> Scope<'s>
+    where
+      'c: 's
+  {
+    Scope(PhantomData)
+  }
+
+  /// Combine evidence from two scopes into evidence valid in both, whose lifetime is outlived by each of theirs.
+  ///
+  /// ```
+  /// # use genz::*;
+  /// struct Guarded<'c>(Scope<'c>, &'c str);
+  ///
+  /// let name = String::from("genz");
+  /// let len = with_scope(|a| with_scope(|b| Guarded(Scope::meet(a, b), &name).1.len()));
+  ///
+  /// assert_eq!(4, len);
+  /// ```
+  #[inline]
+  pub fn meet<'a, 'b>(_: Scope<'a>, _: Scope<'b>) -> Scope<'c>
+    where
+      'a: 'c,
+      'b: 'c
+  {
+    Scope(PhantomData)
+  }
+}
+
+impl<'c> From<Region<'c>> for Scope<'c>
+{
+  #[inline]
+  fn from(region: Region<'c>) -> Self
+  {
+    region.as_scope()
+  }
+}
+
 /// Invoke `f` with an covariant lifetime marker.
 /// 
 /// Because `f` is defined for an arbitrary lifetime `'c` and `Z` has a fixed lifetime, values referencing `'c` are 
@@ -66,10 +119,47 @@ pub fn with_scope<F, Z>(f: F) -> Z
   f(Scope(PhantomData))
 }
 
+/// A contravariant lifetime marker, for capabilities which accept values living at most as long as `'c`.
+///
+/// The contravariance of `CoScope` means a marker may be lengthened, but not shortened:
+///
+/// ```
+/// # use genz::*;
+/// fn lengthen<'s>(coscope: CoScope<'s>) -> CoScope<'static>
+/// {
+///   coscope
+/// }
+///
+/// with_coscope(|coscope| { let _ = lengthen(coscope); });
+/// ```
+///
+/// ```compile_fail
+/// # use genz::*;
+/// fn shorten<'s>(coscope: CoScope<'static>, _: &'s ()) -> CoScope<'s>
+/// {
+///   coscope // fails because `CoScope` is contravariant
+/// }
+/// ```
+#[repr(transparent)]
+#[derive(Copy, Clone)]
+pub struct CoScope<'c>(PhantomData<fn(&'c ())>);
+
+/// Invoke `f` with a contravariant lifetime marker.
+#[inline]
+pub fn with_coscope<F, Z>(f: F) -> Z
+  where
+    for<'c> F: FnOnce(CoScope<'c>) -> Z
+{
+  f(CoScope(PhantomData))
+}
+
 /// An invariant lifetime marker.
 ///
 /// Region markers are created via the `with_region` function. 
 ///
+/// A region carries no data, so it is `Send` and `Sync`, and branded values may be shared with scoped threads. For
+/// invariants which must stay on one thread, use a `LocalRegion`.
+///
 /// Because `f` is defined for an arbitrary lifetime `'c` and `Z` has a fixed lifetime, values referencing `'c` are 
 /// prevented from escaping the closure:
 ///
@@ -98,7 +188,7 @@ pub fn with_scope<F, Z>(f: F) -> Z
 ///
 /// ```compile_fail
 /// # use genz::*;
-/// # use std::marker::PhantomData;
+/// # use std::{fmt, marker::PhantomData};
 ///
 /// fn same_region<'c>(_: PhantomData<Region<'c>>, _: PhantomData<Region<'c>>)
 /// {}
@@ -111,12 +201,55 @@ pub fn with_scope<F, Z>(f: F) -> Z
 /// with_region(|a| with_region(|b| same_region(as_phantom(a), as_phantom(b))))
 /// ```
 #[repr(transparent)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Region<'c>(PhantomData<&'c mut &'c ()>);
 
+impl fmt::Debug for Region<'_>
+{
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+  {
+    f.write_str("Region")
+  }
+}
+
 /// The static region.
 pub const STATIC_REGION: Region<'static> = Region(PhantomData);
 
+#[inline]
+pub(crate) const unsafe fn unchecked_region<'c>() -> Region<'c>
+{
+  Region(PhantomData)
+}
+
+#[cfg(feature = "unsafe_api")]
+impl<'c> Region<'c>
+{
+  /// Create a region of the lifetime `'c` without invoking a closure, e.g. for a context which is known to be created
+  /// once outside of Rust.
+  ///
+  /// # Safety
+  ///
+  /// Values branded with `'c` rely on every region of `'c` standing for the same context. The caller must ensure that
+  /// `'c` is not the lifetime of any region standing for another context, which rules out `'static`, and lifetimes of
+  /// borrows which may be shared with other regions.
+  #[inline]
+  pub const unsafe fn new_unchecked() -> Self
+  {
+    Region(PhantomData)
+  }
+}
+
+/// The static scope, which can be embedded in `static`s and const tables.
+///
+/// ```
+/// # use genz::*;
+/// struct Entry<'c>(&'static str, Scope<'c>);
+///
+/// static TABLE: [Entry<'static>; 2] = [Entry("a", STATIC_SCOPE), Entry("b", STATIC_SCOPE)];
+/// assert_eq!("b", TABLE[1].0);
+/// ```
+pub const STATIC_SCOPE: Scope<'static> = Scope(PhantomData);
+
 /// Invoke `f` with an invariant lifetime marker.
 /// 
 /// Because `f` is defined for an arbitrary lifetime `'c` and `Z` has a fixed lifetime, values referencing `'c` are 
@@ -134,5 +267,164 @@ pub fn with_region<F, Z>(f: F) -> Z
   where 
     for<'c> F: FnOnce(Region<'c>) -> Z
 {
+  #[cfg(feature = "observe")]
+  let _entered = crate::observe::entered();
   f(Region::<'static>(PhantomData))
-}
\ No newline at end of file
+}
+
+impl<'p> Region<'p>
+{
+  /// Returns covariant evidence of the lifetime, for APIs which only need it to live at least as long as `'p`.
+  ///
+  /// ```
+  /// # use genz::*;
+  /// fn same_scope<'c>(_: Scope<'c>, _: Scope<'c>) {}
+  ///
+  /// // the scopes of distinct regions unify, although the regions do not
+  /// with_region(|a| with_region(|b| same_scope(a.as_scope(), b.as_scope())));
+  /// ```
+  #[inline]
+  pub fn as_scope(self) -> Scope<'p>
+  {
+    Scope(PhantomData)
+  }
+
+  /// Invoke `f` with a child region nested within this one, together with evidence of the nesting.
+  ///
+  /// The child region is distinct from its parent, and the evidence lets APIs accept any region nested within a
+  /// particular parent:
+  ///
+  /// ```
+  /// # use genz::*;
+  /// struct Session<'p>(Region<'p>);
+  /// struct Frame<'p, 'c>(Region<'p>, Region<'c>);
+  ///
+  /// impl<'p> Session<'p> {
+  ///   fn frame<'c>(&self, nested: ChildOf<'p, 'c>) -> Frame<'p, 'c> {
+  ///     Frame(nested.parent(), nested.child())
+  ///   }
+  /// }
+  ///
+  /// with_region(|app| {
+  ///   let session = Session(app);
+  ///   app.child(|_, nested| {
+  ///     let _frame = session.frame(nested);
+  ///   });
+  /// });
+  /// ```
+  ///
+  /// The evidence cannot be used the other way around:
+  ///
+  /// ```compile_fail
+  /// # use genz::*;
+  /// fn nested<'p, 'c>(_: Region<'p>, _: Region<'c>, _: ChildOf<'p, 'c>) {}
+  ///
+  /// with_region(|app| app.child(|frame, witness| nested(frame, app, witness)));
+  /// ```
+  #[inline]
+  pub fn child<F, Z>(self, f: F) -> Z
+    where
+      for<'c> F: FnOnce(Region<'c>, ChildOf<'p, 'c>) -> Z
+  {
+    f(Region(PhantomData), ChildOf(PhantomData))
+  }
+
+  /// Invoke `f` with a child region nested within this one, together with evidence that this region outlives it.
+  ///
+  /// This is `child` under the name used for hierarchical resources, e.g. transactions within a connection:
+  ///
+  /// ```
+  /// # use genz::*;
+  /// struct Connection<'p>(Region<'p>);
+  /// struct Transaction<'p, 'c>(Outlives<'p, 'c>);
+  ///
+  /// impl<'p> Connection<'p> {
+  ///   fn begin<'c>(&self, nested: Outlives<'p, 'c>) -> Transaction<'p, 'c> {
+  ///     Transaction(nested)
+  ///   }
+  /// }
+  ///
+  /// with_region(|region| {
+  ///   let connection = Connection(region);
+  ///   region.nest(|_, nested| {
+  ///     let _transaction = connection.begin(nested);
+  ///   });
+  /// });
+  /// ```
+  #[inline]
+  pub fn nest<F, Z>(self, f: F) -> Z
+    where
+      for<'c> F: FnOnce(Region<'c>, Outlives<'p, 'c>) -> Z
+  {
+    self.child(f)
+  }
+}
+
+/// Evidence that region `'c` is a child of region `'p`, implying that `'p` outlives `'c`.
+#[derive(Copy, Clone)]
+pub struct ChildOf<'p, 'c>(PhantomData<(Region<'p>, Region<'c>, &'c &'p ())>);
+
+/// Evidence that region `'p` outlives its child region `'c`, as returned by `Region::nest`.
+pub type Outlives<'p, 'c> = ChildOf<'p, 'c>;
+
+impl<'p, 'c> ChildOf<'p, 'c>
+{
+  /// The parent region.
+  #[inline]
+  pub fn parent(self) -> Region<'p>
+  {
+    Region(PhantomData)
+  }
+
+  /// The child region.
+  #[inline]
+  pub fn child(self) -> Region<'c>
+  {
+    Region(PhantomData)
+  }
+
+  /// Combine with evidence that `'g` is a child of `'c`, producing evidence that `'g` is a descendant of `'p`.
+  #[inline]
+  pub fn then<'g>(self, _: ChildOf<'c, 'g>) -> ChildOf<'p, 'g>
+  {
+    ChildOf(PhantomData)
+  }
+}
+
+/// Evidence that the lifetimes `'a` and `'b` are distinct brands, so that values branded with one cannot be used as
+/// values branded with the other.
+///
+/// It proves nothing about the data opened with the lifetimes: `Gen::with_both(&a, &a, f)` is accepted, and opens the
+/// same value under both of them.
+#[derive(Copy, Clone)]
+pub struct Distinct<'a, 'b>(PhantomData<(Region<'a>, Region<'b>)>);
+
+impl<'a, 'b> Distinct<'a, 'b>
+{
+  #[inline]
+  pub(crate) fn new() -> Self
+  {
+    Distinct(PhantomData)
+  }
+
+  /// The first region.
+  #[inline]
+  pub fn first(self) -> Region<'a>
+  {
+    Region(PhantomData)
+  }
+
+  /// The second region.
+  #[inline]
+  pub fn second(self) -> Region<'b>
+  {
+    Region(PhantomData)
+  }
+
+  /// The same evidence with the regions swapped.
+  #[inline]
+  pub fn flip(self) -> Distinct<'b, 'a>
+  {
+    Distinct(PhantomData)
+  }
+}
@@ -0,0 +1,143 @@
+//! Rate limiting with branded permits.
+
+use std::{marker::PhantomData, time::{Duration, Instant}};
+use crate::Region;
+
+/// Permission to perform one operation guarded by the `RateLimiter<'c>`.
+///
+/// A permit is neither `Copy` nor `Clone`, and performing an operation consumes it.
+#[must_use = "dropping a permit wastes the capacity it took from the limiter"]
+pub struct Permit<'c>(PhantomData<Region<'c>>);
+
+/// A token bucket which hands out permits branded with lifetime `'c`.
+///
+/// The bucket holds up to `capacity` tokens and gains one every `refill`, and each permit takes a token. Operations
+/// which require a `Permit<'c>` cannot be reached without going through the limiter:
+///
+/// ```
+/// # use genz::*;
+/// # use std::time::Duration;
+/// struct Client(Vec<&'static str>);
+///
+/// with_limiter(2, Duration::from_secs(3600), |mut limiter| {
+///   let mut client = Limited::new(&limiter, Client(Vec::new()));
+///
+///   for request in ["a", "b", "c"] {
+///     match limiter.try_acquire() {
+///       Some(permit) => client.call(permit, |client| client.0.push(request)),
+///       None => assert_eq!("c", request)
+///     }
+///   }
+///
+///   assert_eq!(vec!["a", "b"], client.get().0);
+/// });
+/// ```
+///
+/// Permits of one limiter cannot be used for operations guarded by another:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// # use std::time::Duration;
+/// with_limiter(1, Duration::from_secs(1), |mut a| {
+///   with_limiter(1, Duration::from_secs(1), |b| {
+///     let mut guarded = Limited::new(&b, ());
+///     guarded.call(a.try_acquire().unwrap(), |_| ()); // fails because `a` and `b` have different lifetimes
+///   });
+/// });
+/// ```
+pub struct RateLimiter<'c>
+{
+  capacity: u32,
+  tokens: u32,
+  refill: Duration,
+  last: Instant,
+  _region: PhantomData<Region<'c>>
+}
+
+/// Invoke `f` with a full limiter of `capacity` tokens, which gains a token every `refill`, branded with an invariant
+/// lifetime.
+#[inline]
+pub fn with_limiter<Z>(capacity: u32, refill: Duration, f: impl for <'c> FnOnce(RateLimiter<'c>) -> Z) -> Z
+{
+  assert!(!refill.is_zero(), "a limiter needs a non-zero refill interval");
+  f(RateLimiter { capacity, tokens: capacity, refill, last: Instant::now(), _region: PhantomData })
+}
+
+impl<'c> RateLimiter<'c>
+{
+  /// Take a token, returning a permit, or return `None` if the bucket is empty.
+  #[inline]
+  pub fn try_acquire(&mut self) -> Option<Permit<'c>>
+  {
+    self.update();
+    (self.tokens > 0).then(|| {
+      self.tokens -= 1;
+      Permit(PhantomData)
+    })
+  }
+
+  /// The number of tokens in the bucket.
+  #[inline]
+  pub fn available(&mut self) -> u32
+  {
+    self.update();
+    self.tokens
+  }
+
+  /// The maximum number of tokens in the bucket.
+  #[inline]
+  pub fn capacity(&self) -> u32
+  {
+    self.capacity
+  }
+
+  fn update(&mut self)
+  {
+    let gained = self.last.elapsed().as_nanos() / self.refill.as_nanos();
+    if self.tokens as u128 + gained >= self.capacity as u128 {
+      self.tokens = self.capacity;
+      self.last = Instant::now();
+    } else {
+      self.tokens += gained as u32;
+      self.last += self.refill * gained as u32;
+    }
+  }
+}
+
+/// A value whose operations are guarded by the `RateLimiter<'c>`.
+pub struct Limited<'c, T>
+{
+  inner: T,
+  _region: PhantomData<Region<'c>>
+}
+
+impl<'c, T> Limited<'c, T>
+{
+  /// Guard `inner` with `limiter`.
+  #[inline]
+  pub fn new(_: &RateLimiter<'c>, inner: T) -> Self
+  {
+    Limited { inner, _region: PhantomData }
+  }
+
+  /// Consume `permit` to invoke `f` with a mutable reference to the value.
+  #[inline]
+  pub fn call<R>(&mut self, _: Permit<'c>, f: impl FnOnce(&mut T) -> R) -> R
+  {
+    f(&mut self.inner)
+  }
+
+  /// Returns a reference to the value, for operations which need no permit.
+  #[inline]
+  pub fn get(&self) -> &T
+  {
+    &self.inner
+  }
+
+  /// Consume the guard, returning the value.
+  #[inline]
+  pub fn into_inner(self) -> T
+  {
+    self.inner
+  }
+}
@@ -0,0 +1,73 @@
+//! Markers confined to the thread they were created on.
+
+use std::marker::PhantomData;
+use crate::{with_region, Region, UniqueType};
+
+/// An invariant lifetime marker which is neither `Send` nor `Sync`.
+///
+/// Values branded with a local region cannot leave the thread, which suits invariants tied to a thread, like those of
+/// GUI toolkits:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// with_local_region(|region| {
+///   std::thread::scope(|s| {
+///     s.spawn(move || region); // fails because `LocalRegion` is not `Send`
+///   });
+/// });
+/// ```
+#[derive(Copy, Clone)]
+pub struct LocalRegion<'c>(Region<'c>, PhantomData<*const ()>);
+
+/// Invoke `f` with a thread-local invariant lifetime marker.
+#[inline]
+pub fn with_local_region<Z>(f: impl for <'c> FnOnce(LocalRegion<'c>) -> Z) -> Z
+{
+  with_region(|region| f(LocalRegion(region, PhantomData)))
+}
+
+/// A type marker which is unique for an invariant lifetime, and is neither `Send` nor `Sync`.
+///
+/// There is no conversion back into a `UniqueType`, so evidence which requires a local marker stays on its thread:
+///
+/// ```
+/// # use genz::*;
+/// struct Window;
+///
+/// fn repaint<'c>(_: &LocalUniqueType<'c, Window>) -> &'static str
+/// {
+///   "repainted"
+/// }
+///
+/// with_type::<Window, _>(|ty| {
+///   let window = LocalUniqueType::new(ty);
+///   assert_eq!("repainted", repaint(&window));
+///   window.discard();
+/// });
+/// ```
+#[must_use = "dropping a type marker gives up the uniqueness of its type; use `discard` if this is intended"]
+pub struct LocalUniqueType<'c, T: ?Sized>(UniqueType<'c, T>, PhantomData<*const ()>);
+
+impl<'c, T: ?Sized> LocalUniqueType<'c, T>
+{
+  /// Confine `marker` to the current thread.
+  #[inline]
+  pub fn new(marker: UniqueType<'c, T>) -> Self
+  {
+    LocalUniqueType(marker, PhantomData)
+  }
+
+  /// The region of the marker.
+  #[inline]
+  pub fn region(&self) -> LocalRegion<'c>
+  {
+    LocalRegion(self.0.as_type_ref().region(), PhantomData)
+  }
+
+  /// Explicitly drop the marker.
+  #[inline]
+  pub fn discard(self)
+  {
+    self.0.discard()
+  }
+}
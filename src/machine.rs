@@ -0,0 +1,118 @@
+//! Typestate machines with branded state tokens.
+
+/// Generate a module for a state machine with branded state tokens.
+///
+/// The first state is the initial one. For a machine declared in `mod $name`, the module contains:
+///
+/// * a token type `$state<'c>` per state, which is neither `Copy` nor `Clone`,
+/// * a method `$event(self) -> $to<'c>` on the token of `$from` per transition `$from -> $to on $event`,
+/// * a function `start` which invokes a closure with the initial token, branded with an invariant lifetime,
+/// * an erased `State` enum, with a `fire` method which drives the machine by event names at runtime.
+///
+/// ```
+/// # use genz::*;
+/// state_machine! {
+///   pub mod door {
+///     states { Closed, Open, Locked }
+///     transitions { Closed -> Open on open, Open -> Closed on close, Closed -> Locked on lock, Locked -> Closed on unlock }
+///   }
+/// }
+///
+/// // only a closed door of this run can be locked
+/// fn lock<'c>(door: door::Closed<'c>) -> door::Locked<'c>
+/// {
+///   door.lock()
+/// }
+///
+/// let state = door::start(|closed| door::State::from(lock(closed.open().close())));
+/// assert_eq!(door::State::Locked, state);
+///
+/// assert_eq!(Some(door::State::Open), door::State::INITIAL.fire("open"));
+/// assert_eq!(None, door::State::Open.fire("lock"));
+/// ```
+///
+/// Tokens are consumed by transitions:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// # state_machine! { mod door { states { Closed, Open } transitions { Closed -> Open on open } } }
+/// door::start(|closed| {
+///   let _ = closed.open();
+///   let _ = closed.open(); // fails because `closed` was moved
+/// });
+/// ```
+#[macro_export]
+macro_rules! state_machine {
+  (
+    $(#[$meta:meta])*
+    $vis:vis mod $name:ident {
+      states { $initial:ident $(, $state:ident)* $(,)? }
+      transitions { $($from:ident -> $to:ident on $event:ident),* $(,)? }
+    }
+  ) => {
+    $(#[$meta])*
+    $vis mod $name
+    {
+      /// The states of the machine, with their brands erased.
+      #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+      pub enum State
+      {
+        $initial,
+        $($state),*
+      }
+
+      impl State
+      {
+        /// The initial state.
+        pub const INITIAL: State = State::$initial;
+
+        /// Returns the state reached from this one on the event named `event`, or `None` if there is no such
+        /// transition.
+        #[allow(clippy::match_single_binding)]
+        pub fn fire(self, event: &str) -> Option<State>
+        {
+          match (self, event) {
+            $((State::$from, stringify!($event)) => Some(State::$to),)*
+            _ => None
+          }
+        }
+      }
+
+      $crate::state_machine!(@token $initial);
+      $($crate::state_machine!(@token $state);)*
+
+      /// Invoke `f` with the token of the initial state, branded with an invariant lifetime.
+      #[inline]
+      pub fn start<Z>(f: impl for <'c> FnOnce($initial<'c>) -> Z) -> Z
+      {
+        f($initial(::std::marker::PhantomData))
+      }
+
+      $(
+        impl<'c> $from<'c>
+        {
+          #[doc = concat!("Move from `", stringify!($from), "` to `", stringify!($to), "`.")]
+          #[inline]
+          pub fn $event(self) -> $to<'c>
+          {
+            $to(::std::marker::PhantomData)
+          }
+        }
+      )*
+    }
+  };
+
+  (@token $state:ident) => {
+    #[doc = concat!("The token of state `", stringify!($state), "`, branded with lifetime `'c`.")]
+    pub struct $state<'c>(::std::marker::PhantomData<$crate::Region<'c>>);
+
+    impl From<$state<'_>> for State
+    {
+      #[inline]
+      fn from(_: $state<'_>) -> State
+      {
+        State::$state
+      }
+    }
+  };
+}
@@ -0,0 +1,136 @@
+//! Macros creating regions.
+
+use std::marker::PhantomData;
+use crate::Region;
+
+/// Bind `$name` to a region with a fresh invariant lifetime, tied to the enclosing block.
+///
+/// Unlike `with_region`, no closure is needed, so the region may be used with early returns and `?`:
+///
+/// ```
+/// # use genz::*;
+/// struct Digits;
+///
+/// impl Validator<&str> for Digits {
+///   type Error = &'static str;
+///
+///   fn validate(value: &&str) -> Result<(), &'static str>
+///   {
+///     if value.bytes().all(|b| b.is_ascii_digit()) { Ok(()) } else { Err("not digits") }
+///   }
+/// }
+///
+/// fn parse(input: &str) -> Result<u64, &'static str>
+/// {
+///   make_region!(region);
+///   let digits = Validated::<_, Digits>::new(region, input)?;
+///   digits.get().parse().map_err(|_| "too long")
+/// }
+///
+/// assert_eq!(Ok(42), parse("42"));
+/// assert!(parse("4x").is_err());
+/// ```
+///
+/// Each invocation creates a distinct lifetime:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// fn same<'c>(_: Region<'c>, _: Region<'c>) {}
+///
+/// make_region!(a);
+/// make_region!(b);
+/// same(a, b); // fails because the lifetimes are distinct
+/// ```
+///
+/// The region cannot escape the enclosing block:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// let escaped = {
+///   make_region!(region);
+///   region
+/// }; // fails because `region` is borrowed until the end of the block
+/// ```
+#[macro_export]
+macro_rules! make_region {
+  ($name:ident) => {
+    let place = $crate::__make_region::Place;
+    // SAFETY: `place` and `_brand` cannot be named outside the macro, so the brand is dropped at the end of the block,
+    // after any later region of the block has ended.
+    let ($name, _brand) = unsafe { $crate::__make_region::brand(&place) };
+  };
+}
+
+/// Evaluate `$body` with each `$name` bound to a region, all of mutually distinct invariant lifetimes.
+///
+/// ```
+/// # use genz::*;
+/// struct Buffer<'c>(Region<'c>, Vec<u8>);
+///
+/// fn copy<'s, 'o>(source: &Buffer<'s>, output: &mut Buffer<'o>)
+/// {
+///   output.1.extend_from_slice(&source.1);
+/// }
+///
+/// let len = with_regions!(s, o => {
+///   let source = Buffer(s, b"genz".to_vec());
+///   let mut output = Buffer(o, Vec::new());
+///   copy(&source, &mut output);
+///   output.1.len()
+/// });
+///
+/// assert_eq!(4, len);
+/// ```
+///
+/// ```compile_fail
+/// # use genz::*;
+/// fn same<'c>(_: Region<'c>, _: Region<'c>) {}
+///
+/// with_regions!(a, b, c => { same(a, c) }); // fails because the lifetimes are distinct
+/// ```
+#[macro_export]
+macro_rules! with_regions {
+  ($($name:ident),+ $(,)? => $body:block) => {
+    $crate::with_regions!(@nest [$($name)+] $body)
+  };
+
+  (@nest [$first:ident $($rest:ident)*] $body:block) => {
+    $crate::with_region(|$first| $crate::with_regions!(@nest [$($rest)*] $body))
+  };
+
+  (@nest [] $body:block) => {
+    $body
+  };
+}
+
+#[doc(hidden)]
+pub mod __make_region
+{
+  use super::*;
+
+  /// The local place a region made by `make_region!` borrows.
+  pub struct Place;
+
+  pub struct Brand<'c>(PhantomData<Region<'c>>);
+
+  // the drop keeps the borrow of the place alive until the end of the block
+  impl Drop for Brand<'_>
+  {
+    #[inline]
+    fn drop(&mut self) {}
+  }
+
+  /// Returns a region whose lifetime is the borrow of `place`, together with the brand which keeps it borrowed.
+  ///
+  /// # Safety
+  ///
+  /// `place` must be a local of the calling block, and the brand must be held until the end of the block, so the
+  /// lifetime ends after any region made later within the block and before any region made earlier.
+  #[inline]
+  pub unsafe fn brand<'c>(place: &'c Place) -> (Region<'c>, Brand<'c>)
+  {
+    let _ = place;
+    // SAFETY: see above.
+    (unsafe { crate::lifetime::unchecked_region() }, Brand(PhantomData))
+  }
+}
@@ -0,0 +1,169 @@
+//! Markers for static lists of types, checked for distinctness at compile time.
+
+use crate::{with_region, Here, Region, There, UniqueType};
+
+mod sealed
+{
+  use crate::Region;
+
+  // The region passed down while minting, which only this crate can create.
+  pub struct Minting<'c>(pub(crate) Region<'c>);
+}
+
+/// A list of types, which are at the positions `Here`, `There<Here>` and so on of `Self`, up to `Len`.
+///
+/// Implemented by the structs generated by `const_types!`.
+pub trait ConstTypes: Sized
+{
+  /// The number of types, as the position after the last one.
+  type Len: MintMarkers<Self, Here>;
+}
+
+/// The type at the position `I` of the list `Self`.
+///
+/// The type must be at the position `I` according to `IndexOf`, so no type is at two positions.
+pub trait TypeAt<I>
+{
+  /// The type.
+  type Type: ?Sized + IndexOf<Self, Index = I> + 'static;
+}
+
+/// The position of `Self` in the list `S`.
+///
+/// As a type has at most one implementation for each list, it has at most one position.
+pub trait IndexOf<S: ?Sized>
+{
+  /// The position.
+  type Index;
+}
+
+/// Minting of the markers for the `Self` types of the list `S` from the position `I` on, counted as `Here` for none
+/// and `There<R>` for one more than `R`.
+pub trait MintMarkers<S, I>
+{
+  /// The markers, as nested pairs ending with `()`.
+  type Markers<'c>;
+
+  /// Mint the markers, which only `with_const_types` can call.
+  fn mint(minting: sealed::Minting<'_>) -> Self::Markers<'_>;
+}
+
+impl<S, I> MintMarkers<S, I> for Here
+{
+  type Markers<'c> = ();
+
+  #[inline]
+  fn mint(_: sealed::Minting<'_>) {}
+}
+
+impl<S, I, R> MintMarkers<S, I> for There<R>
+  where
+    S: TypeAt<I>,
+    R: MintMarkers<S, There<I>>
+{
+  type Markers<'c> = (UniqueType<'c, <S as TypeAt<I>>::Type>, R::Markers<'c>);
+
+  #[inline]
+  fn mint(minting: sealed::Minting<'_>) -> Self::Markers<'_>
+  {
+    #[cfg(feature = "observe")]
+    crate::observe::minted::<<S as TypeAt<I>>::Type>();
+    let region = minting.0;
+    // SAFETY: `minting` is only created by `with_const_types` for a new region, from which each position of `S` is
+    // minted once, as the positions only increase, and each type is at one position of `S` only.
+    (unsafe { UniqueType::unchecked(region) }, R::mint(sealed::Minting(region)))
+  }
+}
+
+/// Invoke `f` with a region and markers for every type of the list `S`, which are unique for an invariant lifetime.
+///
+/// `const_types!` generates the lists, and calls this with them.
+#[inline]
+pub fn with_const_types<S: ConstTypes, Z>(f: impl for <'c> FnOnce(Region<'c>, <S::Len as MintMarkers<S, Here>>::Markers<'c>) -> Z) -> Z
+{
+  with_region(|region| f(region, S::Len::mint(sealed::Minting(region))))
+}
+
+/// Generate a struct of type markers, one per field, which are unique for an invariant lifetime.
+///
+/// The distinctness of the types is checked at compile time, so a repeated type is a compile error rather than a
+/// runtime failure, the markers are minted without checks at runtime, and there is no limit on the number of types. The struct gets an associated function `with`, which
+/// invokes a closure with a region and the markers:
+///
+/// ```
+/// # use genz::*;
+/// struct Config;
+/// struct Logger;
+/// struct Metrics;
+///
+/// const_types! {
+///   pub struct Services { config: Config, logger: Logger, metrics: Metrics }
+/// }
+///
+/// fn configure<'c>(_: &UniqueType<'c, Config>, _: &UniqueType<'c, Logger>) -> &'static str
+/// {
+///   "configured"
+/// }
+///
+/// assert_eq!("configured", Services::with(|_, services| configure(&services.config, &services.logger)));
+/// ```
+///
+/// ```compile_fail
+/// # use genz::*;
+/// const_types! {
+///   struct Services { first: u8, second: u8 } // fails because `u8` is repeated
+/// }
+/// ```
+#[macro_export]
+macro_rules! const_types {
+  (
+    $(#[$meta:meta])*
+    $vis:vis struct $name:ident { $($field:ident : $ty:ty),* $(,)? }
+  ) => {
+    $(#[$meta])*
+    $vis struct $name<'c>
+    {
+      $(
+        #[doc = concat!("The marker for `", stringify!($ty), "`.")]
+        pub $field: $crate::UniqueType<'c, $ty>,
+      )*
+    }
+
+    impl $name<'_>
+    {
+      /// Invoke `f` with a region and markers for every type, which are unique for an invariant lifetime.
+      #[inline]
+      $vis fn with<Z>(f: impl for <'c> FnOnce($crate::Region<'c>, $name<'c>) -> Z) -> Z
+      {
+        // the list of the types, for which conflicting implementations of `IndexOf` are rejected if a type is repeated
+        struct List;
+        $crate::const_types!(@positions List, $crate::Here; $($ty),*);
+
+        $crate::with_const_types::<List, _>(|region, $crate::const_types!(@pattern $($field),*)| {
+          f(region, $name { $($field),* })
+        })
+      }
+    }
+  };
+  (@positions $list:ident, $index:ty;) => {
+    impl $crate::ConstTypes for $list
+    {
+      type Len = $index;
+    }
+  };
+  (@positions $list:ident, $index:ty; $ty:ty $(, $rest:ty)*) => {
+    impl $crate::TypeAt<$index> for $list
+    {
+      type Type = $ty;
+    }
+
+    impl $crate::IndexOf<$list> for $ty
+    {
+      type Index = $index;
+    }
+
+    $crate::const_types!(@positions $list, $crate::There<$index>; $($rest),*);
+  };
+  (@pattern) => { () };
+  (@pattern $field:ident $(, $rest:ident)*) => { ($field, $crate::const_types!(@pattern $($rest),*)) };
+}
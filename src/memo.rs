@@ -0,0 +1,305 @@
+//! Memoization keyed by branded ids.
+
+use crate::{BiMapId, Brand, Col, FenwickIndex, NodeId, Region, Row, VecHandle};
+
+mod sealed
+{
+  pub trait Sealed {}
+}
+
+/// A branded id which is a dense index into the structure it belongs to.
+///
+/// The trait is sealed, and implemented by the ids of the structures of this crate.
+pub trait DenseId: Copy + sealed::Sealed
+{
+  /// The brand of the structure.
+  type Brand: Brand;
+
+  /// The position of the id in its structure.
+  fn index(self) -> usize;
+
+  /// The brand of the structure the id belongs to.
+  fn brand(self) -> Self::Brand;
+}
+
+impl<B: Brand> sealed::Sealed for FenwickIndex<B> {}
+
+impl<B: Brand> DenseId for FenwickIndex<B>
+{
+  type Brand = B;
+
+  #[inline]
+  fn index(self) -> usize
+  {
+    FenwickIndex::index(self)
+  }
+
+  #[inline]
+  fn brand(self) -> B
+  {
+    FenwickIndex::brand(self)
+  }
+}
+
+impl<B: Brand> sealed::Sealed for Row<B> {}
+
+impl<B: Brand> DenseId for Row<B>
+{
+  type Brand = B;
+
+  #[inline]
+  fn index(self) -> usize
+  {
+    Row::index(self)
+  }
+
+  #[inline]
+  fn brand(self) -> B
+  {
+    Row::brand(self)
+  }
+}
+
+impl<B: Brand> sealed::Sealed for Col<B> {}
+
+impl<B: Brand> DenseId for Col<B>
+{
+  type Brand = B;
+
+  #[inline]
+  fn index(self) -> usize
+  {
+    Col::index(self)
+  }
+
+  #[inline]
+  fn brand(self) -> B
+  {
+    Col::brand(self)
+  }
+}
+
+impl<'c, K> sealed::Sealed for NodeId<'c, K> {}
+
+impl<'c, K> DenseId for NodeId<'c, K>
+{
+  type Brand = Region<'c>;
+
+  #[inline]
+  fn index(self) -> usize
+  {
+    NodeId::index(self)
+  }
+
+  #[inline]
+  fn brand(self) -> Region<'c>
+  {
+    NodeId::brand(self)
+  }
+}
+
+impl<B: Brand> sealed::Sealed for BiMapId<B> {}
+
+impl<B: Brand> DenseId for BiMapId<B>
+{
+  type Brand = B;
+
+  #[inline]
+  fn index(self) -> usize
+  {
+    BiMapId::index(self)
+  }
+
+  #[inline]
+  fn brand(self) -> B
+  {
+    BiMapId::brand(self)
+  }
+}
+
+impl<B: Brand> sealed::Sealed for VecHandle<B> {}
+
+impl<B: Brand> DenseId for VecHandle<B>
+{
+  type Brand = B;
+
+  #[inline]
+  fn index(self) -> usize
+  {
+    VecHandle::index(self)
+  }
+
+  #[inline]
+  fn brand(self) -> B
+  {
+    VecHandle::brand(self)
+  }
+}
+
+enum Slot<V>
+{
+  Empty,
+  Computing,
+  Done(V)
+}
+
+/// A table of values computed at most once per id, for recursive passes over branded structures.
+///
+/// ```
+/// # use genz::*;
+/// // the number of paths from each row to the last row of a graph stored as an adjacency matrix
+/// fn paths<B: Brand>(graph: &Csr<B, ()>, memo: &mut Memo<Row<B>, u64>, row: Row<B>) -> u64
+/// {
+///   *memo.get_or_compute(row, |memo| {
+///     if row.index() == graph.nrows() - 1 {
+///       return 1;
+///     }
+///     graph.entries(row).map(|(col, _)| paths(graph, memo, graph.row(col.index()).unwrap())).sum()
+///   }).expect("the graph is acyclic")
+/// }
+///
+/// let count = with_csr(3, vec![0, 2, 3, 3], vec![1, 2, 2], vec![(); 3], |graph| {
+///   paths(&graph, &mut Memo::new(), graph.row(0).unwrap())
+/// });
+///
+/// assert_eq!(Some(2), count);
+/// ```
+///
+/// Reentrant computations of the same id are detected:
+///
+/// ```
+/// # use genz::*;
+/// with_bimap(|mut names| {
+///   let id = names.insert("x");
+///   let mut memo = Memo::new();
+///   assert_eq!(Some(&true), memo.get_or_compute(id, |memo| memo.get_or_compute(id, |_| false).is_none()));
+/// });
+/// ```
+///
+/// The table records the brand of the first id it is used with, so ids of structures branded with different
+/// `DynRegion`s cannot share it:
+///
+/// ```should_panic
+/// # use genz::*;
+/// let (mut a, mut b) = (BiMap::new(), BiMap::new());
+/// let mut memo = Memo::new();
+///
+/// memo.get_or_compute(a.insert("x"), |_| 1);
+/// memo.get(b.insert("y")); // panics because the ids are of different maps
+/// ```
+pub struct Memo<I: DenseId, V>
+{
+  slots: Vec<Slot<V>>,
+  brand: Option<I::Brand>
+}
+
+impl<I: DenseId, V> Default for Memo<I, V>
+{
+  #[inline]
+  fn default() -> Self
+  {
+    Memo { slots: Vec::new(), brand: None }
+  }
+}
+
+impl<I: DenseId, V> Memo<I, V>
+{
+  /// Create an empty table.
+  #[inline]
+  pub fn new() -> Self
+  {
+    Self::default()
+  }
+
+  /// Returns the value computed for `id`, or `None` if it has not been computed.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `id` is of another structure than the ids used with the table before, which can only happen for brands
+  /// checked at runtime.
+  #[inline]
+  pub fn get(&self, id: I) -> Option<&V>
+  {
+    self.check(id);
+    match self.slots.get(id.index()) {
+      Some(Slot::Done(value)) => Some(value),
+      _ => None
+    }
+  }
+
+  /// Returns the value for `id`, computing it by invoking `f` with the table if it has not been computed.
+  ///
+  /// If `id` is already being computed, `None` is returned. If `f` panics, `id` is left uncomputed, so it may be
+  /// computed again once the panic is caught:
+  ///
+  /// ```
+  /// # use genz::*;
+  /// # use std::panic::{catch_unwind, AssertUnwindSafe};
+  /// with_bimap(|mut names| {
+  ///   let id = names.insert("x");
+  ///   let mut memo = Memo::new();
+  ///
+  ///   assert!(catch_unwind(AssertUnwindSafe(|| { memo.get_or_compute(id, |_| panic!("failed")); })).is_err());
+  ///   assert_eq!(Some(&1), memo.get_or_compute(id, |_| 1));
+  ///
+  ///   // the table may also be replaced while `id` is computed
+  ///   let other = names.insert("y");
+  ///   assert_eq!(Some(&2), memo.get_or_compute(other, |memo| { *memo = Memo::new(); 2 }));
+  /// });
+  /// ```
+  ///
+  /// # Panics
+  ///
+  /// Panics if `id` is of another structure than the ids used with the table before, which can only happen for brands
+  /// checked at runtime.
+  pub fn get_or_compute(&mut self, id: I, f: impl FnOnce(&mut Self) -> V) -> Option<&V>
+  {
+    struct Reset<'a, I: DenseId, V>(&'a mut Memo<I, V>, usize);
+
+    impl<I: DenseId, V> Drop for Reset<'_, I, V>
+    {
+      fn drop(&mut self)
+      {
+        // `f` may have replaced or shrunk the table
+        if let Some(slot @ Slot::Computing) = self.0.slots.get_mut(self.1) {
+          *slot = Slot::Empty;
+        }
+      }
+    }
+
+    self.check(id);
+    self.brand = Some(id.brand());
+    let index = id.index();
+    self.reserve(index);
+
+    match self.slots[index] {
+      Slot::Computing => return None,
+      Slot::Empty => {
+        self.slots[index] = Slot::Computing;
+        let reset = Reset(self, index);
+        let value = f(reset.0);
+        reset.0.reserve(index);
+        reset.0.slots[index] = Slot::Done(value);
+      }
+      Slot::Done(_) => {}
+    }
+
+    self.get(id)
+  }
+
+  #[inline]
+  fn check(&self, id: I)
+  {
+    if let Some(brand) = self.brand {
+      assert!(brand.same_brand(id.brand()), "the id is of another structure");
+    }
+  }
+
+  #[inline]
+  fn reserve(&mut self, index: usize)
+  {
+    if index >= self.slots.len() {
+      self.slots.resize_with(index + 1, || Slot::Empty);
+    }
+  }
+}
@@ -0,0 +1,67 @@
+//! Indices with a niche, so that optional handles are no larger than their index.
+
+use std::{cmp::Ordering, fmt, num::NonZeroU32};
+
+// The largest number of elements of a structure whose handles hold a `NonMaxU32`, so that every index is less than
+// `u32::MAX`.
+pub(crate) const MAX_LEN: usize = u32::MAX as usize;
+
+// An index which is not `u32::MAX`, stored as its complement so that `Option<NonMaxU32>` is 4 bytes.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct NonMaxU32(NonZeroU32);
+
+impl NonMaxU32
+{
+  // Returns `index`, or `None` if it is not less than `MAX_LEN`.
+  #[inline]
+  pub(crate) fn new(index: usize) -> Option<Self>
+  {
+    u32::try_from(index).ok().and_then(|index| NonZeroU32::new(!index)).map(NonMaxU32)
+  }
+
+  // `index` must be less than `MAX_LEN`.
+  #[inline]
+  pub(crate) const unsafe fn new_unchecked(index: usize) -> Self
+  {
+    // SAFETY: `index < u32::MAX`, so its complement as a `u32` is not zero.
+    NonMaxU32(unsafe { NonZeroU32::new_unchecked(!(index as u32)) })
+  }
+
+  #[inline]
+  pub(crate) const fn raw(self) -> u32
+  {
+    !self.0.get()
+  }
+
+  #[inline]
+  pub(crate) const fn get(self) -> usize
+  {
+    self.raw() as usize
+  }
+}
+
+impl PartialOrd for NonMaxU32
+{
+  #[inline]
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering>
+  {
+    Some(self.cmp(other))
+  }
+}
+
+impl Ord for NonMaxU32
+{
+  #[inline]
+  fn cmp(&self, other: &Self) -> Ordering
+  {
+    self.raw().cmp(&other.raw())
+  }
+}
+
+impl fmt::Debug for NonMaxU32
+{
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+  {
+    self.raw().fmt(f)
+  }
+}
@@ -0,0 +1,89 @@
+//! Hooks for observing regions and type markers.
+
+use std::{sync::OnceLock, time::{Duration, Instant}};
+
+/// A hook which is called when regions are entered and exited with `with_region`, and when type markers are minted.
+///
+/// Every method does nothing by default. An observer is installed once for the whole program with `set_observer`:
+///
+/// ```
+/// # use genz::*;
+/// use std::{sync::atomic::{AtomicUsize, Ordering}, time::Duration};
+///
+/// struct Counter(AtomicUsize);
+///
+/// impl RegionObserver for Counter {
+///   fn type_minted(&self, _: &'static str)
+///   {
+///     self.0.fetch_add(1, Ordering::Relaxed);
+///   }
+/// }
+///
+/// static COUNTER: Counter = Counter(AtomicUsize::new(0));
+/// set_observer(&COUNTER).ok().unwrap();
+///
+/// with_type::<u8, _>(|ty| ty.discard());
+/// with_types::<(u8, u16), _>(|_, (t1, t2)| { t1.discard(); t2.discard(); });
+///
+/// assert_eq!(3, COUNTER.0.load(Ordering::Relaxed));
+/// ```
+pub trait RegionObserver: Sync
+{
+  /// Called when a region is entered.
+  #[inline]
+  fn region_entered(&self) {}
+
+  /// Called when a region is exited, with the time spent in it.
+  #[inline]
+  fn region_exited(&self, elapsed: Duration)
+  {
+    let _ = elapsed;
+  }
+
+  /// Called when a type marker is minted, with the name of its type as given by `std::any::type_name`.
+  #[inline]
+  fn type_minted(&self, type_name: &'static str)
+  {
+    let _ = type_name;
+  }
+}
+
+static OBSERVER: OnceLock<&'static dyn RegionObserver> = OnceLock::new();
+
+/// Install the global observer, or return `observer` if one is already installed.
+#[inline]
+pub fn set_observer(observer: &'static dyn RegionObserver) -> Result<(), &'static dyn RegionObserver>
+{
+  OBSERVER.set(observer)
+}
+
+/// Reports the exit of a region to the observer, even if the region is exited by unwinding.
+pub(crate) struct Entered(Option<(&'static dyn RegionObserver, Instant)>);
+
+#[inline]
+pub(crate) fn entered() -> Entered
+{
+  Entered(OBSERVER.get().map(|observer| {
+    observer.region_entered();
+    (*observer, Instant::now())
+  }))
+}
+
+impl Drop for Entered
+{
+  #[inline]
+  fn drop(&mut self)
+  {
+    if let Some((observer, start)) = self.0 {
+      observer.region_exited(start.elapsed());
+    }
+  }
+}
+
+#[inline]
+pub(crate) fn minted<T: ?Sized>()
+{
+  if let Some(observer) = OBSERVER.get() {
+    observer.type_minted(std::any::type_name::<T>());
+  }
+}
@@ -0,0 +1,76 @@
+//! Opening several stored values at once.
+
+use crate::{Gen, Storable};
+
+/// A trait implemented by tuples of `&Gen<_>`, and tuples of `&mut Gen<_>`, which may be opened in a single closure.
+///
+/// Each value is opened with its own invariant lifetime, so values of one cannot be confused with values of another:
+///
+/// ```
+/// # use genz::*;
+/// struct Counter<'c> {
+///   _ty: UniqueType<'c, Counter<'static>>,
+///   count: u32
+/// }
+///
+/// impl Storable for Counter<'static> {
+///   type Generative<'c> = Counter<'c>;
+/// }
+///
+/// let mut a = Gen::<Counter>::from_type(|_ty| Counter { _ty, count: 1 });
+/// let mut b = Gen::<Counter>::from_type(|_ty| Counter { _ty, count: 2 });
+///
+/// (&mut a, &mut b).open(|a, b| std::mem::swap(&mut a.count, &mut b.count));
+///
+/// assert_eq!((2, 1), (&a, &b).open(|a, b| (a.count, b.count)));
+/// ```
+///
+/// ```compile_fail
+/// # use genz::*;
+/// let mut a = Gen::<UniqueType<u8>>::from_type(|ty| ty);
+/// let mut b = Gen::<UniqueType<u8>>::from_type(|ty| ty);
+///
+/// // fails because `a` and `b` are opened with different lifetimes
+/// (&mut a, &mut b).open(|a, b| std::mem::swap(a, b));
+/// ```
+pub trait OpenAll<F, R>
+{
+  /// Invoke `f` with every value in the tuple.
+  fn open(self, f: F) -> R;
+}
+
+macro_rules! open_all {
+  ($(($tt:ident, $gen:ident, $lt:lifetime)),+) => {
+    impl<$($tt: Storable,)+ F, R> OpenAll<F, R> for ($(&Gen<$tt>,)+)
+      where
+        F: for <$($lt),+> FnOnce($(&$tt::Generative<$lt>),+) -> R
+    {
+      #[inline]
+      fn open(self, f: F) -> R
+      {
+        let ($($gen,)+) = self;
+        f($($gen.0.borrow()),+)
+      }
+    }
+
+    impl<$($tt: Storable,)+ F, R> OpenAll<F, R> for ($(&mut Gen<$tt>,)+)
+      where
+        F: for <$($lt),+> FnOnce($(&mut $tt::Generative<$lt>),+) -> R
+    {
+      #[inline]
+      fn open(self, f: F) -> R
+      {
+        let ($($gen,)+) = self;
+        f($($gen.0.borrow_mut()),+)
+      }
+    }
+  };
+}
+
+open_all!((T0, g0, 'c0), (T1, g1, 'c1));
+open_all!((T0, g0, 'c0), (T1, g1, 'c1), (T2, g2, 'c2));
+open_all!((T0, g0, 'c0), (T1, g1, 'c1), (T2, g2, 'c2), (T3, g3, 'c3));
+open_all!((T0, g0, 'c0), (T1, g1, 'c1), (T2, g2, 'c2), (T3, g3, 'c3), (T4, g4, 'c4));
+open_all!((T0, g0, 'c0), (T1, g1, 'c1), (T2, g2, 'c2), (T3, g3, 'c3), (T4, g4, 'c4), (T5, g5, 'c5));
+open_all!((T0, g0, 'c0), (T1, g1, 'c1), (T2, g2, 'c2), (T3, g3, 'c3), (T4, g4, 'c4), (T5, g5, 'c5), (T6, g6, 'c6));
+open_all!((T0, g0, 'c0), (T1, g1, 'c1), (T2, g2, 'c2), (T3, g3, 'c3), (T4, g4, 'c4), (T5, g5, 'c5), (T6, g6, 'c6), (T7, g7, 'c7));
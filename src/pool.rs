@@ -0,0 +1,162 @@
+//! Object pools with branded leases.
+
+use crate::{with_region, Brand, DynRegion, Region};
+
+/// Exclusive use of an object in the `Pool<B, _>`.
+///
+/// A lease is neither `Copy` nor `Clone`, and returning it to the pool consumes it, so a recycled object can never be
+/// reached through a stale lease.
+#[must_use = "dropping a lease keeps its object out of the pool; return it with `Pool::release`"]
+pub struct Lease<B>(usize, B);
+
+/// A pool of reusable objects, leased out with handles of brand `B`.
+///
+/// ```
+/// # use genz::*;
+/// with_pool(|mut pool| {
+///   let a = pool.acquire(Vec::new);
+///   pool.get_mut(&a).push(1);
+///   pool.release(a);
+///
+///   // the released object is recycled
+///   let b = pool.acquire(Vec::new);
+///   assert_eq!(vec![1], *pool.get(&b));
+///   pool.release(b);
+/// });
+/// ```
+///
+/// Leases must be returned to the pool they came from:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// with_pool(|mut a: Pool<_, u8>| {
+///   with_pool(|mut b: Pool<_, u8>| {
+///     let lease = a.acquire(|| 0);
+///     b.release(lease); // fails because `a` and `b` have different lifetimes
+///   });
+/// });
+/// ```
+///
+/// and cannot be used once returned:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// with_pool(|mut pool| {
+///   let lease = pool.acquire(|| 0);
+///   pool.release(lease);
+///   *pool.get_mut(&lease) += 1; // fails because `lease` was moved
+/// });
+/// ```
+///
+/// A pool branded with a `DynRegion` checks at runtime that leases are returned to it:
+///
+/// ```should_panic
+/// # use genz::*;
+/// let mut a = Pool::new();
+/// let mut b = Pool::<_, i32>::new();
+/// let lease = a.acquire(|| 0);
+/// b.release(lease); // panics because the lease belongs to `a`
+/// ```
+pub struct Pool<B, T>
+{
+  brand: B,
+  objects: Vec<T>,
+  free: Vec<usize>
+}
+
+/// Invoke `f` with an empty pool branded with an invariant lifetime.
+#[inline]
+pub fn with_pool<T, Z>(f: impl for <'c> FnOnce(Pool<Region<'c>, T>) -> Z) -> Z
+{
+  with_region(|region| f(Pool { brand: region, objects: Vec::new(), free: Vec::new() }))
+}
+
+impl<T> Pool<DynRegion, T>
+{
+  /// Create an empty pool branded with a new `DynRegion`.
+  #[inline]
+  #[allow(clippy::new_without_default)]
+  pub fn new() -> Self
+  {
+    Pool { brand: DynRegion::new(), objects: Vec::new(), free: Vec::new() }
+  }
+}
+
+impl<B: Brand, T> Pool<B, T>
+{
+  /// The brand of the pool.
+  #[inline]
+  pub fn brand(&self) -> B
+  {
+    self.brand
+  }
+
+  /// Lease a free object, creating one with `make` if there are none.
+  #[inline]
+  pub fn acquire(&mut self, make: impl FnOnce() -> T) -> Lease<B>
+  {
+    let index = self.free.pop().unwrap_or_else(|| {
+      self.objects.push(make());
+      self.objects.len() - 1
+    });
+    Lease(index, self.brand)
+  }
+
+  /// Return the leased object to the pool.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `lease` is of another pool, which can only happen for brands checked at runtime.
+  #[inline]
+  pub fn release(&mut self, lease: Lease<B>)
+  {
+    self.check(&lease);
+    self.free.push(lease.0);
+  }
+
+  /// Returns a reference to the leased object.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `lease` is of another pool, which can only happen for brands checked at runtime.
+  #[inline]
+  pub fn get(&self, lease: &Lease<B>) -> &T
+  {
+    self.check(lease);
+    // SAFETY: `lease` was returned by `acquire` on this pool, and objects are never removed.
+    unsafe { self.objects.get_unchecked(lease.0) }
+  }
+
+  /// Returns a mutable reference to the leased object.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `lease` is of another pool, which can only happen for brands checked at runtime.
+  #[inline]
+  pub fn get_mut(&mut self, lease: &Lease<B>) -> &mut T
+  {
+    self.check(lease);
+    // SAFETY: `lease` was returned by `acquire` on this pool, and objects are never removed.
+    unsafe { self.objects.get_unchecked_mut(lease.0) }
+  }
+
+  /// The number of objects which are not leased.
+  #[inline]
+  pub fn available(&self) -> usize
+  {
+    self.free.len()
+  }
+
+  /// The number of objects owned by the pool, whether leased or not.
+  #[inline]
+  pub fn capacity(&self) -> usize
+  {
+    self.objects.len()
+  }
+
+  #[inline]
+  fn check(&self, lease: &Lease<B>)
+  {
+    assert!(self.brand.same_brand(lease.1), "the lease is of another pool");
+  }
+}
@@ -0,0 +1,260 @@
+//! Read-mostly values whose versions are branded with invariant lifetimes.
+
+use std::{marker::PhantomData, ops::Deref, sync::{atomic::{AtomicU64, Ordering}, Arc, RwLock}};
+use crate::Region;
+
+struct Version<T>
+{
+  value: Arc<T>,
+  epoch: u64
+}
+
+/// A read-mostly value shared between readers and writers, e.g. the configuration of a server.
+///
+/// Writers publish new versions through a shared reference, while readers keep using the versions they entered with.
+/// A read section invokes a closure with a snapshot of a pinned version, branded with a fresh invariant lifetime.
+/// Because every `Snapshot<'c, T>` of one section shares its lifetime, they are known to be of the same version
+/// without comparing epochs.
+///
+/// `Rcu::read` pins the current version for a single section, which takes the lock of the slot and a reference count
+/// increment. For a read path with no atomics, a `Reader` keeps its version pinned across sections, and only picks up
+/// newer versions at the quiescent points where it is refreshed:
+///
+/// ```
+/// # use genz::*;
+/// struct Config { limit: u32 }
+///
+/// // both snapshots are of the same version, because they share a lifetime
+/// fn total<'c>(a: Snapshot<'c, Config>, b: Snapshot<'c, Config>) -> u32
+/// {
+///   a.limit + b.limit
+/// }
+///
+/// let config = Rcu::new(Config { limit: 8 });
+///
+/// std::thread::scope(|s| {
+///   s.spawn(|| config.read(|snapshot| assert!(matches!(total(snapshot, snapshot), 16 | 32))));
+///   config.update(|old| Config { limit: old.limit * 2 });
+/// });
+///
+/// config.read(|snapshot| {
+///   // publishing does not affect the version of a section which was already entered
+///   config.publish(Config { limit: 0 });
+///   assert_eq!((1, 16), (snapshot.epoch(), snapshot.limit));
+/// });
+///
+/// assert_eq!(2, config.epoch());
+/// ```
+///
+/// ```
+/// # use genz::*;
+/// # struct Config { limit: u32 }
+/// let config = Rcu::new(Config { limit: 8 });
+/// let mut reader = config.reader();
+///
+/// std::thread::scope(|s| {
+///   s.spawn(|| config.publish(Config { limit: 16 }));
+///
+///   // sections of the reader take no atomics, and see the pinned version until it is refreshed
+///   assert_eq!(8, reader.read(|snapshot| snapshot.limit));
+/// });
+///
+/// assert!(reader.refresh());
+/// assert_eq!(16, reader.read(|snapshot| snapshot.limit));
+/// ```
+///
+/// Snapshots of different sections cannot be mixed:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// # struct Config { limit: u32 }
+/// # fn total<'c>(_: Snapshot<'c, Config>, _: Snapshot<'c, Config>) {}
+/// let config = Rcu::new(Config { limit: 8 });
+/// config.read(|old| config.read(|new| total(old, new))); // fails because the snapshots may be of different versions
+/// ```
+///
+/// nor escape their section:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// let config = Rcu::new(8);
+/// let snapshot = config.read(|snapshot| snapshot); // fails because the snapshot is branded with the section
+/// ```
+pub struct Rcu<T>
+{
+  current: RwLock<Version<T>>,
+  latest: AtomicU64
+}
+
+/// A reader of an `Rcu<T>` which keeps a version pinned between quiescent points.
+///
+/// Sections entered through the reader take no atomics. Refreshing needs exclusive access to the reader, so no
+/// snapshot of the pinned version is alive when it is replaced:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// let config = Rcu::new(8);
+/// let mut reader = config.reader();
+/// reader.read(|snapshot| { reader.refresh(); *snapshot }); // fails because the section borrows the reader
+/// ```
+pub struct Reader<'r, T>
+{
+  rcu: &'r Rcu<T>,
+  value: Arc<T>,
+  epoch: u64
+}
+
+/// A version of the value in an `Rcu<T>`, pinned for the read section of lifetime `'c`.
+pub struct Snapshot<'c, T>
+{
+  value: &'c T,
+  epoch: u64,
+  _region: PhantomData<Region<'c>>
+}
+
+impl<T> Rcu<T>
+{
+  /// Create a slot whose first version is `value`.
+  #[inline]
+  pub fn new(value: T) -> Self
+  {
+    Rcu { current: RwLock::new(Version { value: Arc::new(value), epoch: 0 }), latest: AtomicU64::new(0) }
+  }
+
+  /// Create a reader which pins the current version.
+  #[inline]
+  pub fn reader(&self) -> Reader<'_, T>
+  {
+    let (value, epoch) = self.with_current(|current| (Arc::clone(&current.value), current.epoch));
+    Reader { rcu: self, value, epoch }
+  }
+
+  /// Enter a read section, invoking `f` with a snapshot of the current version branded with a fresh invariant
+  /// lifetime.
+  ///
+  /// The version stays alive until `f` returns, even if newer versions are published in the meantime. Pinning it takes
+  /// the lock of the slot and a reference count increment, which a `Reader` avoids.
+  pub fn read<Z>(&self, f: impl for <'c> FnOnce(Snapshot<'c, T>) -> Z) -> Z
+  {
+    let (value, epoch) = self.with_current(|current| (Arc::clone(&current.value), current.epoch));
+    f(Snapshot { value: &value, epoch, _region: PhantomData })
+  }
+
+  /// Returns the current version, for readers which keep it beyond a read section.
+  #[inline]
+  pub fn load(&self) -> Arc<T>
+  {
+    self.with_current(|current| Arc::clone(&current.value))
+  }
+
+  /// The number of versions published before the current one.
+  #[inline]
+  pub fn epoch(&self) -> u64
+  {
+    self.with_current(|current| current.epoch)
+  }
+
+  /// Publish `value` as the next version, returning its epoch.
+  ///
+  /// Read sections which were already entered keep their versions, and later ones see `value`.
+  #[inline]
+  pub fn publish(&self, value: T) -> u64
+  {
+    self.update(|_| value)
+  }
+
+  /// Publish the value returned by `f` for the current version as the next version, returning its epoch.
+  ///
+  /// Writers are serialized, so no version published by another writer is lost, while readers are only blocked for
+  /// the time it takes to swap versions.
+  pub fn update(&self, f: impl FnOnce(&T) -> T) -> u64
+  {
+    let mut current = self.current.write().unwrap_or_else(|err| err.into_inner());
+    let value = Arc::new(f(&current.value));
+    let epoch = current.epoch + 1;
+    let old = std::mem::replace(&mut *current, Version { value, epoch });
+    self.latest.store(epoch, Ordering::Release);
+    // the old version may be the last reference to its value, which is dropped without holding the lock
+    drop(current);
+    drop(old);
+    epoch
+  }
+
+  #[inline]
+  fn with_current<R>(&self, f: impl FnOnce(&Version<T>) -> R) -> R
+  {
+    f(&self.current.read().unwrap_or_else(|err| err.into_inner()))
+  }
+}
+
+impl<T> Reader<'_, T>
+{
+  /// Enter a read section, invoking `f` with a snapshot of the pinned version branded with a fresh invariant lifetime.
+  ///
+  /// No atomic operations are performed.
+  #[inline]
+  pub fn read<Z>(&self, f: impl for <'c> FnOnce(Snapshot<'c, T>) -> Z) -> Z
+  {
+    f(Snapshot { value: &self.value, epoch: self.epoch, _region: PhantomData })
+  }
+
+  /// The epoch of the pinned version.
+  #[inline]
+  pub fn epoch(&self) -> u64
+  {
+    self.epoch
+  }
+
+  /// Mark a quiescent point, pinning the current version if a newer one was published, and returning `true` if so.
+  ///
+  /// The pinned version is released, so versions no reader pins any more are dropped.
+  pub fn refresh(&mut self) -> bool
+  {
+    if self.rcu.latest.load(Ordering::Acquire) == self.epoch {
+      return false;
+    }
+    let (value, epoch) = self.rcu.with_current(|current| (Arc::clone(&current.value), current.epoch));
+    self.value = value;
+    self.epoch = epoch;
+    true
+  }
+}
+
+impl<'c, T> Snapshot<'c, T>
+{
+  /// The number of versions published before this one.
+  #[inline]
+  pub fn epoch(self) -> u64
+  {
+    self.epoch
+  }
+
+  /// Returns a reference to the value, valid for the rest of the read section.
+  #[inline]
+  pub fn get(self) -> &'c T
+  {
+    self.value
+  }
+}
+
+impl<T> Clone for Snapshot<'_, T>
+{
+  #[inline]
+  fn clone(&self) -> Self
+  {
+    *self
+  }
+}
+
+impl<T> Copy for Snapshot<'_, T> {}
+
+impl<T> Deref for Snapshot<'_, T>
+{
+  type Target = T;
+
+  #[inline]
+  fn deref(&self) -> &T
+  {
+    self.value
+  }
+}
@@ -0,0 +1,90 @@
+//! Stored values resolved by type.
+
+use std::{any::{Any, TypeId}, collections::HashMap};
+use crate::{Gen, Storable};
+
+/// A registry storing at most one `Gen` per storable type.
+///
+/// Useful for application-level singletons of branded subsystems which are resolved by type:
+///
+/// ```
+/// # use genz::*;
+/// struct Logger<'c> {
+///   _ty: UniqueType<'c, Logger<'static>>,
+///   lines: Vec<&'static str>
+/// }
+///
+/// impl Storable for Logger<'static> {
+///   type Generative<'c> = Logger<'c>;
+/// }
+///
+/// let mut registry = TypedGenRegistry::new();
+/// registry.insert(Gen::<Logger<'static>>::from_type(|_ty| Logger { _ty, lines: Vec::new() }));
+///
+/// registry.with_mut::<Logger<'static>, _>(|logger| logger.lines.push("hello"));
+/// assert_eq!(Some(1), registry.with_ref::<Logger<'static>, _>(|logger| logger.lines.len()));
+/// assert_eq!(None, registry.with_ref::<UniqueType<'static, u8>, _>(|_| ()));
+/// ```
+#[derive(Default)]
+pub struct TypedGenRegistry(HashMap<TypeId, Box<dyn Any>>);
+
+impl TypedGenRegistry
+{
+  /// Create an empty registry.
+  #[inline]
+  pub fn new() -> Self
+  {
+    Self::default()
+  }
+
+  /// Store `gen`, returning the previously stored value of the same type, if any.
+  pub fn insert<Z: Storable + 'static>(&mut self, gen: Gen<Z>) -> Option<Gen<Z>>
+  {
+    self.0.insert(TypeId::of::<Z>(), Box::new(gen)).map(Self::downcast)
+  }
+
+  /// Remove and return the stored value of type `Z`, if any.
+  pub fn remove<Z: Storable + 'static>(&mut self) -> Option<Gen<Z>>
+  {
+    self.0.remove(&TypeId::of::<Z>()).map(Self::downcast)
+  }
+
+  /// Returns `true` if a value of type `Z` is stored.
+  #[inline]
+  pub fn contains<Z: Storable + 'static>(&self) -> bool
+  {
+    self.0.contains_key(&TypeId::of::<Z>())
+  }
+
+  /// Returns the stored value of type `Z`, if any.
+  pub fn get<Z: Storable + 'static>(&self) -> Option<&Gen<Z>>
+  {
+    self.0.get(&TypeId::of::<Z>()).and_then(|gen| gen.downcast_ref())
+  }
+
+  /// Returns the stored value of type `Z` mutably, if any.
+  pub fn get_mut<Z: Storable + 'static>(&mut self) -> Option<&mut Gen<Z>>
+  {
+    self.0.get_mut(&TypeId::of::<Z>()).and_then(|gen| gen.downcast_mut())
+  }
+
+  /// Invoke `f` with a reference to the stored value of type `Z`, or return `None` if there is no such value.
+  #[inline]
+  pub fn with_ref<Z: Storable + 'static, R>(&self, f: impl for <'c> FnOnce(&Z::Generative<'c>) -> R) -> Option<R>
+  {
+    self.get::<Z>().map(|gen| gen.with_ref(f))
+  }
+
+  /// Invoke `f` with a mutable reference to the stored value of type `Z`, or return `None` if there is no such value.
+  #[inline]
+  pub fn with_mut<Z: Storable + 'static, R>(&mut self, f: impl for <'c> FnOnce(&mut Z::Generative<'c>) -> R) -> Option<R>
+  {
+    self.get_mut::<Z>().map(|gen| gen.with_mut(f))
+  }
+
+  #[inline]
+  fn downcast<Z: 'static>(gen: Box<dyn Any>) -> Gen<Z>
+  {
+    *gen.downcast().expect("values are keyed by their own type")
+  }
+}
@@ -0,0 +1,94 @@
+//! Storage for a value together with a view borrowing from it.
+
+use std::{mem::ManuallyDrop, ptr::NonNull};
+use crate::{lifetime::STATIC_REGION, Region, Storable};
+
+/// A boxed owner together with a view which borrows from it.
+///
+/// The view is a `Storable` whose generative form borrows the owner for lifetime `'c`. It is only accessible through
+/// closures which are defined for an arbitrary `'c`, so the borrow cannot outlive the owner, and it is always dropped
+/// before the owner is:
+///
+/// ```
+/// # use genz::*;
+/// struct Words<'c>(Vec<&'c str>);
+///
+/// impl Storable for Words<'static> {
+///   type Generative<'c> = Words<'c>;
+/// }
+///
+/// let words = SelfRef::<str, Words>::new("hello branded world".into(), |text, _| {
+///   Words(text.split(' ').collect())
+/// });
+///
+/// assert_eq!("branded", words.with_ref(|_, words| words.0[1].to_owned()));
+/// ```
+///
+/// ```compile_fail
+/// # use genz::*;
+/// # struct Words<'c>(Vec<&'c str>);
+/// # impl Storable for Words<'static> { type Generative<'c> = Words<'c>; }
+/// let words = SelfRef::<str, Words>::new("hello".into(), |text, _| Words(vec![text]));
+///
+/// let word = words.with_ref(|_, words| words.0[0]);
+/// drop(words);
+/// println!("{}", word); // fails because the borrow cannot escape the closure
+/// ```
+pub struct SelfRef<T: ?Sized, Z>
+{
+  view: ManuallyDrop<Z>,
+  owner: NonNull<T>
+}
+
+impl<T: ?Sized + 'static, Z: Storable> SelfRef<T, Z>
+{
+  /// Store `owner` together with the view created by invoking `f` with a reference to it.
+  pub fn new(owner: Box<T>, f: impl for <'c> FnOnce(&'c T, Region<'c>) -> Z::Generative<'c>) -> Self
+  {
+    // SAFETY: the pointer comes from `Box::into_raw`, so it is non-null.
+    let owner = unsafe { NonNull::new_unchecked(Box::into_raw(owner)) };
+    // SAFETY: the owner is only freed when `self` is dropped, after the view.
+    let view = f(unsafe { owner.as_ref() }, STATIC_REGION).into();
+    SelfRef { view: ManuallyDrop::new(view), owner }
+  }
+
+  /// Invoke `f` with a reference to the owner and to the view.
+  #[inline]
+  pub fn with_ref<R>(&self, f: impl for <'c> FnOnce(&'c T, &Z::Generative<'c>) -> R) -> R
+  {
+    // SAFETY: the owner is live until `self` is dropped, and is never borrowed mutably.
+    f(unsafe { self.owner.as_ref() }, (*self.view).borrow())
+  }
+
+  /// Invoke `f` with a reference to the owner and a mutable reference to the view.
+  #[inline]
+  pub fn with_mut<R>(&mut self, f: impl for <'c> FnOnce(&'c T, &mut Z::Generative<'c>) -> R) -> R
+  {
+    // SAFETY: the owner is live until `self` is dropped, and is never borrowed mutably.
+    f(unsafe { self.owner.as_ref() }, (*self.view).borrow_mut())
+  }
+
+  /// Drop the view and return the owner.
+  #[inline]
+  pub fn into_owner(self) -> Box<T>
+  {
+    let mut this = ManuallyDrop::new(self);
+    // SAFETY: `this` is never dropped, so the view is dropped exactly once, before the owner is reclaimed.
+    unsafe {
+      ManuallyDrop::drop(&mut this.view);
+      Box::from_raw(this.owner.as_ptr())
+    }
+  }
+}
+
+impl<T: ?Sized, Z> Drop for SelfRef<T, Z>
+{
+  fn drop(&mut self)
+  {
+    // SAFETY: the view, which may borrow the owner, is dropped first, and neither is used again.
+    unsafe {
+      ManuallyDrop::drop(&mut self.view);
+      drop(Box::from_raw(self.owner.as_ptr()));
+    }
+  }
+}
@@ -0,0 +1,145 @@
+//! Shared ownership of stored values.
+
+use std::{rc::Rc, sync::{Arc, RwLock}};
+use crate::{Gen, Storable};
+
+/// A reference-counted stored value.
+///
+/// Each access opens the value with a fresh invariant lifetime, so clones may be handed to separate subsystems while
+/// their brands stay apart. Mutable access is only granted while the value is not shared:
+///
+/// ```
+/// # use genz::*;
+/// struct Interner<'c>(Region<'c>, Vec<&'static str>);
+///
+/// impl Storable for Interner<'static> {
+///   type Generative<'c> = Interner<'c>;
+/// }
+///
+/// let mut interner = GenRc::new(Gen::<Interner>::from_type::<()>(|ty| Interner(ty.into(), Vec::new())));
+/// assert_eq!(Some(()), interner.with_mut(|interner| interner.1.push("a")));
+///
+/// let parser = interner.clone();
+/// assert_eq!(None, interner.with_mut(|interner| interner.1.push("b")));
+/// assert_eq!(1, parser.with_ref(|interner| interner.1.len()));
+/// ```
+pub struct GenRc<Z>(Rc<Gen<Z>>);
+
+impl<Z: Storable> GenRc<Z>
+{
+  /// Share `gen`.
+  #[inline]
+  pub fn new(gen: Gen<Z>) -> Self
+  {
+    GenRc(Rc::new(gen))
+  }
+
+  /// Invoke `f` with a reference to the value.
+  #[inline]
+  pub fn with_ref<R>(&self, f: impl for <'c> FnOnce(&Z::Generative<'c>) -> R) -> R
+  {
+    self.0.with_ref(f)
+  }
+
+  /// Invoke `f` with a mutable reference to the value, or return `None` if the value is shared.
+  #[inline]
+  pub fn with_mut<R>(&mut self, f: impl for <'c> FnOnce(&mut Z::Generative<'c>) -> R) -> Option<R>
+  {
+    Rc::get_mut(&mut self.0).map(|gen| gen.with_mut(f))
+  }
+
+  /// Returns the stored value if it is not shared, or `self` otherwise.
+  #[inline]
+  pub fn try_unwrap(self) -> Result<Gen<Z>, Self>
+  {
+    Rc::try_unwrap(self.0).map_err(GenRc)
+  }
+}
+
+impl<Z> Clone for GenRc<Z>
+{
+  #[inline]
+  fn clone(&self) -> Self
+  {
+    GenRc(Rc::clone(&self.0))
+  }
+}
+
+impl<Z: Storable> From<Gen<Z>> for GenRc<Z>
+{
+  #[inline]
+  fn from(gen: Gen<Z>) -> Self
+  {
+    GenRc::new(gen)
+  }
+}
+
+/// An atomically reference-counted stored value, which may be shared between threads.
+///
+/// Like `GenRc`, each access opens the value with a fresh invariant lifetime. Accesses are guarded by a read-write
+/// lock, so mutable access is granted while the value is shared:
+///
+/// ```
+/// # use genz::*;
+/// struct Interner<'c>(Region<'c>, Vec<&'static str>);
+///
+/// impl Storable for Interner<'static> {
+///   type Generative<'c> = Interner<'c>;
+/// }
+///
+/// let interner = GenArc::new(Gen::<Interner>::from_type::<()>(|ty| Interner(ty.into(), Vec::new())));
+/// let worker = interner.clone();
+///
+/// std::thread::spawn(move || worker.with_mut(|interner| interner.1.push("a"))).join().unwrap();
+/// assert_eq!(1, interner.with_ref(|interner| interner.1.len()));
+/// ```
+pub struct GenArc<Z>(Arc<RwLock<Gen<Z>>>);
+
+impl<Z: Storable> GenArc<Z>
+{
+  /// Share `gen`.
+  #[inline]
+  pub fn new(gen: Gen<Z>) -> Self
+  {
+    GenArc(Arc::new(RwLock::new(gen)))
+  }
+
+  /// Invoke `f` with a reference to the value, blocking while it is mutably accessed.
+  #[inline]
+  pub fn with_ref<R>(&self, f: impl for <'c> FnOnce(&Z::Generative<'c>) -> R) -> R
+  {
+    self.0.read().unwrap_or_else(|err| err.into_inner()).with_ref(f)
+  }
+
+  /// Invoke `f` with a mutable reference to the value, blocking while it is accessed.
+  #[inline]
+  pub fn with_mut<R>(&self, f: impl for <'c> FnOnce(&mut Z::Generative<'c>) -> R) -> R
+  {
+    self.0.write().unwrap_or_else(|err| err.into_inner()).with_mut(f)
+  }
+
+  /// Returns the stored value if it is not shared, or `self` otherwise.
+  #[inline]
+  pub fn try_unwrap(self) -> Result<Gen<Z>, Self>
+  {
+    Arc::try_unwrap(self.0).map(|lock| lock.into_inner().unwrap_or_else(|err| err.into_inner())).map_err(GenArc)
+  }
+}
+
+impl<Z> Clone for GenArc<Z>
+{
+  #[inline]
+  fn clone(&self) -> Self
+  {
+    GenArc(Arc::clone(&self.0))
+  }
+}
+
+impl<Z: Storable> From<Gen<Z>> for GenArc<Z>
+{
+  #[inline]
+  fn from(gen: Gen<Z>) -> Self
+  {
+    GenArc::new(gen)
+  }
+}
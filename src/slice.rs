@@ -0,0 +1,346 @@
+//! Slices whose windows carry branded proofs of bounds and disjointness.
+
+use std::{ops::Range, slice};
+use crate::{with_region, Brand, DynRegion, Region};
+
+/// An in-bounds range of the `BrandedSlice<'_, B, _>`.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Window<B>
+{
+  start: usize,
+  end: usize,
+  brand: B
+}
+
+/// Two windows of the `BrandedSlice<'_, B, _>` which are known not to overlap.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Disjoint<B>(Window<B>, Window<B>);
+
+/// An iterator over the overlapping windows of a fixed length of the `BrandedSlice<'_, B, _>`.
+#[derive(Clone, Debug)]
+pub struct Windows<B>
+{
+  brand: B,
+  next: usize,
+  size: usize,
+  len: usize
+}
+
+/// An iterator over every window of a `Windows<B>`, paired with the first window after it which it does not overlap.
+#[derive(Clone, Debug)]
+pub struct DisjointPairs<B>(Windows<B>);
+
+/// A mutable slice whose windows carry the brand `B`.
+///
+/// Windows are checked to be in-bounds once when they are created, and pairs of windows are checked to be disjoint once
+/// when their `Disjoint<B>` proof is created, after which both windows can be mutated at the same time without further
+/// checks. `Windows::disjoint_pairs` yields such proofs for adjacent windows, e.g. for an odd-even transposition sort:
+///
+/// ```
+/// # use genz::*;
+/// let mut values = [5, 1, 4, 2, 3];
+///
+/// with_branded_slice(&mut values, |mut slice| {
+///   for round in 0 .. slice.len() {
+///     for pair in slice.windows_branded(1).disjoint_pairs().skip(round % 2).step_by(2) {
+///       let (a, b) = slice.pair_mut(pair);
+///       if a[0] > b[0] {
+///         std::mem::swap(&mut a[0], &mut b[0]);
+///       }
+///     }
+///   }
+/// });
+///
+/// assert_eq!([1, 2, 3, 4, 5], values);
+/// ```
+///
+/// or a stencil which folds each block into the block after it:
+///
+/// ```
+/// # use genz::*;
+/// let mut cells = [1, 2, 3, 4, 5, 6];
+///
+/// with_branded_slice(&mut cells, |mut slice| {
+///   for pair in slice.windows_branded(2).disjoint_pairs().step_by(2) {
+///     let (block, next) = slice.pair_mut(pair);
+///     next.iter_mut().zip(block).for_each(|(cell, value)| *cell += *value);
+///   }
+///
+///   let (first, last) = (slice.window(0 .. 2).unwrap(), slice.window(4 .. 6).unwrap());
+///   assert!(first.disjoint(last).is_some());
+///   assert!(first.disjoint(slice.full()).is_none());
+/// });
+///
+/// assert_eq!([1, 2, 4, 6, 9, 12], cells);
+/// ```
+///
+/// Windows of one slice cannot be used with another:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// let (mut a, mut b) = ([0], [0]);
+/// with_branded_slice(&mut a, |a| {
+///   with_branded_slice(&mut b, |mut b| {
+///     b.get_mut(a.full()); // fails because `a` and `b` have different lifetimes
+///   });
+/// });
+/// ```
+///
+/// A slice created by `BrandedSlice::new` is branded with a `DynRegion` instead, and using a window of another slice
+/// panics:
+///
+/// ```should_panic
+/// # use genz::*;
+/// let (mut a, mut b) = ([0], [0]);
+/// let (a, mut b) = (BrandedSlice::new(&mut a), BrandedSlice::new(&mut b));
+/// b.get_mut(a.full()); // panics because the window belongs to `a`
+/// ```
+pub struct BrandedSlice<'s, B, T>
+{
+  slice: &'s mut [T],
+  brand: B
+}
+
+/// Invoke `f` with `slice` as a branded slice, branded with an invariant lifetime.
+#[inline]
+pub fn with_branded_slice<'s, T, Z>(slice: &'s mut [T], f: impl for <'c> FnOnce(BrandedSlice<'s, Region<'c>, T>) -> Z) -> Z
+{
+  with_region(|region| f(BrandedSlice { slice, brand: region }))
+}
+
+impl<'s, T> BrandedSlice<'s, DynRegion, T>
+{
+  /// Create a branded slice of `slice`, branded with a new `DynRegion`.
+  #[inline]
+  pub fn new(slice: &'s mut [T]) -> Self
+  {
+    BrandedSlice { slice, brand: DynRegion::new() }
+  }
+}
+
+impl<B: Brand, T> BrandedSlice<'_, B, T>
+{
+  /// The brand of the slice.
+  #[inline]
+  pub fn brand(&self) -> B
+  {
+    self.brand
+  }
+
+  /// The number of elements.
+  #[inline]
+  pub fn len(&self) -> usize
+  {
+    self.slice.len()
+  }
+
+  /// Returns `true` if the slice has no elements.
+  #[inline]
+  pub fn is_empty(&self) -> bool
+  {
+    self.slice.is_empty()
+  }
+
+  /// The window covering the whole slice.
+  #[inline]
+  pub fn full(&self) -> Window<B>
+  {
+    Window { start: 0, end: self.slice.len(), brand: self.brand }
+  }
+
+  /// Returns the window for `range`, or `None` if it is out of bounds.
+  #[inline]
+  pub fn window(&self, range: Range<usize>) -> Option<Window<B>>
+  {
+    (range.start <= range.end && range.end <= self.slice.len())
+      .then_some(Window { start: range.start, end: range.end, brand: self.brand })
+  }
+
+  /// Returns an iterator over the overlapping windows of length `size`, in the order of `slice::windows`.
+  ///
+  /// The iterator does not borrow the slice, so the windows it yields can be mutated while iterating.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `size` is `0`.
+  #[inline]
+  pub fn windows_branded(&self, size: usize) -> Windows<B>
+  {
+    assert!(size > 0, "windows must not be empty");
+    Windows { brand: self.brand, next: 0, size, len: self.slice.len() }
+  }
+
+  /// Returns the elements covered by `window`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `window` is of another slice, which can only happen for brands checked at runtime.
+  #[inline]
+  pub fn get(&self, window: Window<B>) -> &[T]
+  {
+    self.check(window);
+    // SAFETY: `window` was checked to be in-bounds of this slice when it was created.
+    unsafe { self.slice.get_unchecked(window.start .. window.end) }
+  }
+
+  /// Returns the elements covered by `window` mutably.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `window` is of another slice, which can only happen for brands checked at runtime.
+  #[inline]
+  pub fn get_mut(&mut self, window: Window<B>) -> &mut [T]
+  {
+    self.check(window);
+    // SAFETY: `window` was checked to be in-bounds of this slice when it was created.
+    unsafe { self.slice.get_unchecked_mut(window.start .. window.end) }
+  }
+
+  /// Returns the elements covered by both windows of `pair` mutably.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `pair` is of another slice, which can only happen for brands checked at runtime.
+  #[inline]
+  pub fn pair_mut(&mut self, pair: Disjoint<B>) -> (&mut [T], &mut [T])
+  {
+    let Disjoint(a, b) = pair;
+    self.check(a);
+    let ptr = self.slice.as_mut_ptr();
+    // SAFETY: both windows are in-bounds of this slice, and `Disjoint` proves that they do not overlap.
+    unsafe {
+      (slice::from_raw_parts_mut(ptr.add(a.start), a.len()), slice::from_raw_parts_mut(ptr.add(b.start), b.len()))
+    }
+  }
+
+  #[inline]
+  fn check(&self, window: Window<B>)
+  {
+    assert!(self.brand.same_brand(window.brand), "the window is of another slice");
+  }
+}
+
+impl<B: Brand> Window<B>
+{
+  /// The brand of the slice the window belongs to.
+  #[inline]
+  pub fn brand(self) -> B
+  {
+    self.brand
+  }
+
+  /// The range of indices covered.
+  #[inline]
+  pub fn range(self) -> Range<usize>
+  {
+    self.start .. self.end
+  }
+
+  /// The number of elements covered.
+  #[inline]
+  pub fn len(self) -> usize
+  {
+    self.end - self.start
+  }
+
+  /// Returns `true` if the window covers no elements.
+  #[inline]
+  pub fn is_empty(self) -> bool
+  {
+    self.start == self.end
+  }
+
+  /// Returns a proof that `self` and `other` do not overlap, or `None` if they do.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the windows are of different slices, which can only happen for brands checked at runtime.
+  #[inline]
+  pub fn disjoint(self, other: Self) -> Option<Disjoint<B>>
+  {
+    assert!(self.brand.same_brand(other.brand), "the windows are of different slices");
+    (self.end <= other.start || other.end <= self.start).then_some(Disjoint(self, other))
+  }
+}
+
+impl<B: Brand> Disjoint<B>
+{
+  /// The first window of the pair.
+  #[inline]
+  pub fn first(self) -> Window<B>
+  {
+    self.0
+  }
+
+  /// The second window of the pair.
+  #[inline]
+  pub fn second(self) -> Window<B>
+  {
+    self.1
+  }
+}
+
+impl<B: Brand> Windows<B>
+{
+  /// Pair every remaining window with the first window after it which it does not overlap, i.e. the window starting
+  /// where it ends.
+  #[inline]
+  pub fn disjoint_pairs(self) -> DisjointPairs<B>
+  {
+    DisjointPairs(self)
+  }
+
+  #[inline]
+  fn at(&self, start: usize) -> Window<B>
+  {
+    Window { start, end: start + self.size, brand: self.brand }
+  }
+}
+
+impl<B: Brand> Iterator for Windows<B>
+{
+  type Item = Window<B>;
+
+  #[inline]
+  fn next(&mut self) -> Option<Window<B>>
+  {
+    (self.next + self.size <= self.len).then(|| {
+      self.next += 1;
+      self.at(self.next - 1)
+    })
+  }
+
+  #[inline]
+  fn size_hint(&self) -> (usize, Option<usize>)
+  {
+    let len = (self.len + 1).saturating_sub(self.next + self.size);
+    (len, Some(len))
+  }
+}
+
+impl<B: Brand> ExactSizeIterator for Windows<B> {}
+
+impl<B: Brand> Iterator for DisjointPairs<B>
+{
+  type Item = Disjoint<B>;
+
+  #[inline]
+  fn next(&mut self) -> Option<Disjoint<B>>
+  {
+    let windows = &mut self.0;
+    (windows.next + 2 * windows.size <= windows.len).then(|| {
+      windows.next += 1;
+      Disjoint(windows.at(windows.next - 1), windows.at(windows.next - 1 + windows.size))
+    })
+  }
+
+  #[inline]
+  fn size_hint(&self) -> (usize, Option<usize>)
+  {
+    let windows = &self.0;
+    let len = (windows.len + 1).saturating_sub(windows.next + 2 * windows.size);
+    (len, Some(len))
+  }
+}
+
+impl<B: Brand> ExactSizeIterator for DisjointPairs<B> {}
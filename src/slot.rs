@@ -0,0 +1,81 @@
+//! Two-phase initialization of stored values.
+
+use std::{marker::PhantomData, mem::ManuallyDrop};
+use crate::{with_region, DuplicateType, Gen, Region, Storable, TryGenTuple};
+
+/// A reserved lifetime `'c` for a stored value of `Z`, which is filled in later.
+///
+/// The region of the slot is available before the value exists, so branded parts of the value may be built up front,
+/// e.g. while streaming input. Filling the slot consumes it, so it is filled at most once:
+///
+/// ```
+/// # use genz::*;
+/// struct Lines<'c>(Region<'c>, Vec<String>);
+///
+/// impl Storable for Lines<'static> {
+///   type Generative<'c> = Lines<'c>;
+/// }
+///
+/// let lines = GenSlot::<Lines>::with(|slot| {
+///   let region = slot.region();
+///   let mut lines = Vec::new();
+///   for line in "a\nb".lines() {
+///     lines.push(line.to_owned());
+///   }
+///   slot.fill(Lines(region, lines))
+/// });
+///
+/// assert_eq!(2, lines.with_ref(|lines| lines.1.len()));
+/// ```
+///
+/// ```compile_fail
+/// # use genz::*;
+/// # struct Lines<'c>(Region<'c>, Vec<String>);
+/// # impl Storable for Lines<'static> { type Generative<'c> = Lines<'c>; }
+/// GenSlot::<Lines>::with(|slot| {
+///   let region = slot.region();
+///   let _ = slot.fill(Lines(region, Vec::new()));
+///   slot.fill(Lines(region, Vec::new())); // fails because `slot` was moved
+/// });
+/// ```
+pub struct GenSlot<'c, Z>(Region<'c>, PhantomData<fn() -> Z>);
+
+impl<Z: Storable> GenSlot<'_, Z>
+{
+  /// Invoke `f` with an empty slot, reserving an invariant lifetime.
+  #[inline]
+  pub fn with<R>(f: impl for <'c> FnOnce(GenSlot<'c, Z>) -> R) -> R
+  {
+    with_region(|region| f(GenSlot(region, PhantomData)))
+  }
+
+  /// Attempt to invoke `f` with an empty slot and a tuple of type markers that are unique for its lifetime.
+  ///
+  /// If any types in the tuple are duplicates, a `DuplicateType` error is returned.
+  #[inline]
+  pub fn try_with_types<Types: TryGenTuple, R>(f: impl for <'c> FnOnce(GenSlot<'c, Z>, Types::Tuple<'c>) -> R) -> Result<R, DuplicateType>
+  {
+    with_region(|region| Types::try_gen_tuple(region).map(|types| f(GenSlot(region, PhantomData), types)))
+  }
+}
+
+impl<'c, Z: Storable> GenSlot<'c, Z>
+{
+  /// The reserved region.
+  #[inline]
+  pub fn region(&self) -> Region<'c>
+  {
+    self.0
+  }
+
+  /// Fill the slot with `value`, returning the stored value.
+  #[inline]
+  pub fn fill(self, value: Z::Generative<'c>) -> Gen<Z>
+  {
+    let value = ManuallyDrop::new(value);
+    // SAFETY: `Z::Generative<'c>` and `Z::Generative<'static>` differ only in lifetimes, so they have the same layout,
+    // and `value` is not dropped. The lifetime `'c` was reserved for this slot alone, which is consumed.
+    let value: Z::Generative<'static> = unsafe { std::ptr::read((&*value as *const Z::Generative<'c>).cast()) };
+    Gen(value.into())
+  }
+}
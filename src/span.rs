@@ -0,0 +1,180 @@
+//! Spans branded to a specific input buffer.
+
+use std::ops::Range;
+use crate::{with_region, Brand, DynRegion, Region};
+
+/// An input buffer whose spans carry the brand `B`.
+#[derive(Copy, Clone)]
+pub struct Source<'s, B>(&'s str, B);
+
+/// A range of the `Source<'_, B>` which is in-bounds and lies on `char` boundaries.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Span<B>
+{
+  start: usize,
+  end: usize,
+  brand: B
+}
+
+/// Invoke `f` with `text` as a source branded with an invariant lifetime.
+///
+/// Spans are validated once when they are created, after which slicing and looking up their position is infallible:
+///
+/// ```
+/// # use genz::*;
+/// with_source("let x =\n  y;", |source| {
+///   let x = source.span(4 .. 5).unwrap();
+///   let y = source.span(10 .. 11).unwrap();
+///
+///   assert_eq!("x", source.slice(x));
+///   assert_eq!("x =\n  y", source.slice(x.merge(y)));
+///   assert_eq!((2, 3), source.lookup(y));
+///
+///   // `None` is returned because the range is out of bounds
+///   assert!(source.span(4 .. 20).is_none());
+/// });
+/// ```
+///
+/// Spans of one source cannot be used with another:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// with_source("a", |a| {
+///   with_source("b", |b| {
+///     b.slice(a.full()); // fails because `a` and `b` have different lifetimes
+///   });
+/// });
+/// ```
+///
+/// A source created by `Source::new` is branded with a `DynRegion` instead, and using a span of another source panics:
+///
+/// ```should_panic
+/// # use genz::*;
+/// let (a, b) = (Source::new("a"), Source::new("b"));
+/// b.slice(a.full()); // panics because the span belongs to `a`
+/// ```
+#[inline]
+pub fn with_source<'s, Z>(text: &'s str, f: impl for <'c> FnOnce(Source<'s, Region<'c>>) -> Z) -> Z
+{
+  with_region(|region| f(Source(text, region)))
+}
+
+impl<'s> Source<'s, DynRegion>
+{
+  /// Create a source of `text` branded with a new `DynRegion`.
+  #[inline]
+  pub fn new(text: &'s str) -> Self
+  {
+    Source(text, DynRegion::new())
+  }
+}
+
+impl<'s, B: Brand> Source<'s, B>
+{
+  /// The brand of the source.
+  #[inline]
+  pub fn brand(self) -> B
+  {
+    self.1
+  }
+
+  /// The text of the source.
+  #[inline]
+  pub fn text(self) -> &'s str
+  {
+    self.0
+  }
+
+  /// The span covering the whole source.
+  #[inline]
+  pub fn full(self) -> Span<B>
+  {
+    Span { start: 0, end: self.0.len(), brand: self.1 }
+  }
+
+  /// Returns the span for `range`, or `None` if it is out of bounds or does not lie on `char` boundaries.
+  #[inline]
+  pub fn span(self, range: Range<usize>) -> Option<Span<B>>
+  {
+    self.0.get(range.clone()).map(|_| Span { start: range.start, end: range.end, brand: self.1 })
+  }
+
+  /// Returns the text covered by `span`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `span` is of another source, which can only happen for brands checked at runtime.
+  #[inline]
+  pub fn slice(self, span: Span<B>) -> &'s str
+  {
+    self.check(span);
+    // SAFETY: `span` was validated against this source when it was created.
+    unsafe { self.0.get_unchecked(span.start .. span.end) }
+  }
+
+  /// Returns the 1-based line and column, counted in `char`s, at which `span` starts.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `span` is of another source, which can only happen for brands checked at runtime.
+  pub fn lookup(self, span: Span<B>) -> (usize, usize)
+  {
+    self.check(span);
+    // SAFETY: `span.start` lies on a `char` boundary of this source.
+    let before = unsafe { self.0.get_unchecked(.. span.start) };
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let line = before.matches('\n').count() + 1;
+    // SAFETY: `line_start` follows a newline or is `0`, so it lies on a `char` boundary.
+    let column = unsafe { before.get_unchecked(line_start ..) }.chars().count() + 1;
+    (line, column)
+  }
+
+  #[inline]
+  fn check(self, span: Span<B>)
+  {
+    assert!(self.1.same_brand(span.brand), "the span is of another source");
+  }
+}
+
+impl<B: Brand> Span<B>
+{
+  /// The offset in bytes at which the span starts.
+  #[inline]
+  pub fn start(self) -> usize
+  {
+    self.start
+  }
+
+  /// The offset in bytes at which the span ends.
+  #[inline]
+  pub fn end(self) -> usize
+  {
+    self.end
+  }
+
+  /// The length of the span in bytes.
+  #[inline]
+  pub fn len(self) -> usize
+  {
+    self.end - self.start
+  }
+
+  /// Returns `true` if the span is empty.
+  #[inline]
+  pub fn is_empty(self) -> bool
+  {
+    self.start == self.end
+  }
+
+  /// The smallest span covering both `self` and `other`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the spans are of different sources, which can only happen for brands checked at runtime.
+  #[inline]
+  pub fn merge(self, other: Span<B>) -> Span<B>
+  {
+    assert!(self.brand.same_brand(other.brand), "the spans are of different sources");
+    Span { start: self.start.min(other.start), end: self.end.max(other.end), brand: self.brand }
+  }
+}
@@ -0,0 +1,161 @@
+//! A wait-free single-producer single-consumer queue with branded endpoints.
+
+use std::{cell::UnsafeCell, marker::PhantomData, mem::MaybeUninit, sync::atomic::{AtomicUsize, Ordering}};
+use crate::Region;
+
+/// A bounded queue holding up to `N - 1` values, which is split into a producer and a consumer.
+///
+/// The queue only uses `core` atomics, so it is suitable for passing values from an interrupt handler to a main loop.
+/// Both endpoints are branded with the same lifetime, so functions can require a matching pair:
+///
+/// ```
+/// # use genz::*;
+/// fn pump<'c>(producer: &mut Producer<'_, 'c, u32, 4>, consumer: &mut Consumer<'_, 'c, u32, 4>) -> Option<u32>
+/// {
+///   producer.enqueue(1).ok()?;
+///   consumer.dequeue()
+/// }
+///
+/// let mut queue = Queue::<u32, 4>::new();
+/// queue.split(|mut producer, mut consumer| {
+///   assert_eq!(Some(1), pump(&mut producer, &mut consumer));
+///
+///   std::thread::scope(|s| {
+///     s.spawn(move || (0 .. 3).for_each(|i| while producer.enqueue(i).is_err() {}));
+///     let received: Vec<_> = (0 .. 3).map(|_| loop {
+///       if let Some(i) = consumer.dequeue() { break i; }
+///     }).collect();
+///     assert_eq!(vec![0, 1, 2], received);
+///   });
+/// });
+/// ```
+///
+/// Endpoints of different queues cannot be paired:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// # fn pump<'c>(_: &mut Producer<'_, 'c, u32, 4>, _: &mut Consumer<'_, 'c, u32, 4>) {}
+/// let (mut a, mut b) = (Queue::<u32, 4>::new(), Queue::<u32, 4>::new());
+/// a.split(|mut producer, _| b.split(|_, mut consumer| pump(&mut producer, &mut consumer)));
+/// ```
+pub struct Queue<T, const N: usize>
+{
+  buffer: [UnsafeCell<MaybeUninit<T>>; N],
+  head: AtomicUsize,
+  tail: AtomicUsize
+}
+
+// SAFETY: values are only moved between the producer and the consumer, which synchronize through `head` and `tail`.
+unsafe impl<T: Send, const N: usize> Sync for Queue<T, N> {}
+
+/// The endpoint which adds values to the queue of the `Consumer<'q, 'c, T, N>`.
+pub struct Producer<'q, 'c, T, const N: usize>(&'q Queue<T, N>, PhantomData<Region<'c>>);
+
+/// The endpoint which removes values from the queue of the `Producer<'q, 'c, T, N>`.
+pub struct Consumer<'q, 'c, T, const N: usize>(&'q Queue<T, N>, PhantomData<Region<'c>>);
+
+/// A position in the buffer of a queue, which is always less than `N`.
+#[derive(Copy, Clone, PartialEq, Eq)]
+struct Slot<const N: usize>(usize);
+
+impl<const N: usize> Slot<N>
+{
+  #[inline]
+  fn load(index: &AtomicUsize, ordering: Ordering) -> Self
+  {
+    Slot(index.load(ordering))
+  }
+
+  #[inline]
+  fn next(self) -> Self
+  {
+    if self.0 + 1 == N { Slot(0) } else { Slot(self.0 + 1) }
+  }
+}
+
+impl<T, const N: usize> Default for Queue<T, N>
+{
+  #[inline]
+  fn default() -> Self
+  {
+    Self::new()
+  }
+}
+
+impl<T, const N: usize> Queue<T, N>
+{
+  /// Create an empty queue.
+  #[inline]
+  pub const fn new() -> Self
+  {
+    const { assert!(N > 0, "a queue needs at least one slot") };
+    Queue {
+      buffer: [const { UnsafeCell::new(MaybeUninit::uninit()) }; N],
+      head: AtomicUsize::new(0),
+      tail: AtomicUsize::new(0)
+    }
+  }
+
+  /// Invoke `f` with the producer and consumer of the queue, branded with an invariant lifetime.
+  #[inline]
+  pub fn split<'q, Z>(&'q mut self, f: impl for <'c> FnOnce(Producer<'q, 'c, T, N>, Consumer<'q, 'c, T, N>) -> Z) -> Z
+  {
+    f(Producer(self, PhantomData), Consumer(self, PhantomData))
+  }
+
+  #[inline]
+  fn slot(&self, slot: Slot<N>) -> *mut MaybeUninit<T>
+  {
+    // SAFETY: a `Slot<N>` is always less than `N`.
+    unsafe { self.buffer.get_unchecked(slot.0) }.get()
+  }
+}
+
+impl<T, const N: usize> Producer<'_, '_, T, N>
+{
+  /// Add `value` to the queue, or return it if the queue is full.
+  #[inline]
+  pub fn enqueue(&mut self, value: T) -> Result<(), T>
+  {
+    let tail = Slot::<N>::load(&self.0.tail, Ordering::Relaxed);
+    let next = tail.next();
+    if next == Slot::load(&self.0.head, Ordering::Acquire) {
+      return Err(value);
+    }
+    // SAFETY: only the producer writes to the slot at `tail`, which the consumer does not read until `tail` advances.
+    unsafe { (*self.0.slot(tail)).write(value) };
+    self.0.tail.store(next.0, Ordering::Release);
+    Ok(())
+  }
+}
+
+impl<T, const N: usize> Consumer<'_, '_, T, N>
+{
+  /// Remove the oldest value from the queue, or return `None` if it is empty.
+  #[inline]
+  pub fn dequeue(&mut self) -> Option<T>
+  {
+    let head = Slot::<N>::load(&self.0.head, Ordering::Relaxed);
+    if head == Slot::load(&self.0.tail, Ordering::Acquire) {
+      return None;
+    }
+    // SAFETY: the slot at `head` was initialized by the producer before `tail` advanced past it.
+    let value = unsafe { (*self.0.slot(head)).assume_init_read() };
+    self.0.head.store(head.next().0, Ordering::Release);
+    Some(value)
+  }
+}
+
+impl<T, const N: usize> Drop for Queue<T, N>
+{
+  fn drop(&mut self)
+  {
+    let mut head = Slot::<N>(*self.head.get_mut());
+    let tail = Slot::<N>(*self.tail.get_mut());
+    while head != tail {
+      // SAFETY: the slots from `head` up to `tail` hold values which were never dequeued.
+      unsafe { (*self.slot(head)).assume_init_drop() };
+      head = head.next();
+    }
+  }
+}
@@ -1,6 +1,6 @@
-use std::borrow::BorrowMut;
+use std::{borrow::BorrowMut, pin::Pin};
 
-use crate::{Gen, Region, UniqueType};
+use crate::{Gen, Region, SelfRef, UniqueType};
 
 /// The trait of values containing unique types which may be stored.
 ///
@@ -29,14 +29,269 @@ pub trait Storable: BorrowMut<Self::Generative<'static>> + From<Self::Generative
   type Generative<'c>;
 }
 
+/// The trait of storable values whose generative form can be cloned within its region.
+///
+/// `UniqueType` does not implement this trait, because cloning it would break its uniqueness, so only values whose
+/// branded parts are `Copy` markers like `Region` can be cloned:
+///
+/// ```
+/// # use genz::*;
+/// struct Tagged<'c>(Region<'c>, Vec<u32>);
+///
+/// impl Storable for Tagged<'static> {
+///   type Generative<'c> = Tagged<'c>;
+/// }
+///
+/// impl CloneGenerative for Tagged<'static> {
+///   fn clone_generative<'c>(value: &Self::Generative<'c>) -> Self::Generative<'c>
+///   {
+///     Tagged(value.0, value.1.clone())
+///   }
+/// }
+///
+/// let mut original = Gen::<Tagged>::from_type::<()>(|ty| Tagged(ty.into(), vec![1]));
+/// let copy = original.snapshot();
+/// original.with_mut(|t| t.1.push(2));
+///
+/// assert_eq!(vec![1, 2], original.with_ref(|t| t.1.clone()));
+/// assert_eq!(vec![1], copy.with_ref(|t| t.1.clone()));
+/// ```
+pub trait CloneGenerative: Storable
+{
+  /// Returns a deep copy of `value` in the same region.
+  fn clone_generative<'c>(value: &Self::Generative<'c>) -> Self::Generative<'c>;
+}
+
+/// Declares that the stored forms of `Self` and `W` have the same layout, so one may be reinterpreted as the other.
+///
+/// This allows a stored value to be converted without being rebuilt, e.g. when a wrapper newtype is introduced:
+///
+/// ```
+/// # use genz::*;
+/// struct Ids<'c>(UniqueType<'c, u8>, Vec<u32>);
+///
+/// #[repr(transparent)]
+/// struct SortedIds<'c>(Ids<'c>);
+///
+/// impl Storable for Ids<'static> {
+///   type Generative<'c> = Ids<'c>;
+/// }
+///
+/// impl Storable for SortedIds<'static> {
+///   type Generative<'c> = SortedIds<'c>;
+/// }
+///
+/// // SAFETY: `SortedIds` is a transparent wrapper of `Ids`.
+/// unsafe impl SameGenerative<SortedIds<'static>> for Ids<'static> {}
+///
+/// let ids = Gen::<Ids>::from_type(|ty| Ids(ty, vec![1, 2]));
+/// let sorted: Gen<SortedIds> = ids.transmute_storable();
+/// assert_eq!(vec![1, 2], sorted.with_ref(|sorted| sorted.0.1.clone()));
+/// ```
+///
+/// Layout compatibility cannot be checked by the compiler, so the trait is `unsafe` to implement by hand, and there is no
+/// derive. For the common case of a transparent newtype, `same_generative!` declares the wrapper and implements the
+/// trait in both directions without `unsafe` code.
+///
+/// # Safety
+///
+/// `Self` and `W` must have the same size and alignment, and every valid value of `Self` must be a valid value of `W`
+/// with the same meaning for the type markers it contains.
+pub unsafe trait SameGenerative<W: Storable>: Storable {}
+
+/// Declare a `#[repr(transparent)]` wrapper of a storable type, implementing `Storable` for it and `SameGenerative`
+/// between the wrapper and the wrapped type in both directions.
+///
+/// Both types are written with one lifetime parameter, which the macro replaces by `'static` in the stored forms:
+///
+/// ```
+/// # use genz::*;
+/// struct Ids<'c>(UniqueType<'c, u8>, Vec<u32>);
+///
+/// impl Storable for Ids<'static> {
+///   type Generative<'c> = Ids<'c>;
+/// }
+///
+/// same_generative! {
+///   /// Ids in ascending order.
+///   pub struct SortedIds<'c>(pub Ids<'c>);
+/// }
+///
+/// let mut ids = Gen::<Ids>::from_type(|ty| Ids(ty, vec![2, 1]));
+/// ids.with_mut(|ids| ids.1.sort());
+///
+/// let sorted: Gen<SortedIds> = ids.transmute_storable();
+/// let ids: Gen<Ids> = sorted.transmute_storable();
+/// assert_eq!(vec![1, 2], ids.with_ref(|ids| ids.1.clone()));
+/// ```
+#[macro_export]
+macro_rules! same_generative {
+  (
+    $(#[$meta:meta])*
+    $vis:vis struct $name:ident<$lt:lifetime>($field_vis:vis $inner:ident<$inner_lt:lifetime>);
+  ) => {
+    $(#[$meta])*
+    #[repr(transparent)]
+    $vis struct $name<$lt>($field_vis $inner<$inner_lt>);
+
+    impl $crate::Storable for $name<'static> {
+      type Generative<$lt> = $name<$lt>;
+    }
+
+    // SAFETY: the wrapper is transparent, so it has the layout and the markers of the wrapped type.
+    unsafe impl $crate::SameGenerative<$name<'static>> for $inner<'static> {}
+
+    // SAFETY: see above.
+    unsafe impl $crate::SameGenerative<$inner<'static>> for $name<'static> {}
+  };
+}
+
+/// The trait of storable values which have a default generative form, built from a marker for `U`.
+///
+/// ```
+/// # use genz::*;
+/// struct Interner<'c>(Region<'c>, Vec<String>);
+///
+/// impl Storable for Interner<'static> {
+///   type Generative<'c> = Interner<'c>;
+/// }
+///
+/// impl GenerativeDefault for Interner<'static> {
+///   fn default_with<'c>(ty: UniqueType<'c, ()>) -> Self::Generative<'c>
+///   {
+///     Interner(ty.into(), Vec::new())
+///   }
+/// }
+///
+/// let interner = Gen::<Interner>::default();
+/// assert!(interner.with_ref(|interner| interner.1.is_empty()));
+/// ```
+pub trait GenerativeDefault<U: ?Sized = ()>: Storable
+{
+  /// Returns the default value in the region of `ty`.
+  fn default_with<'c>(ty: UniqueType<'c, U>) -> Self::Generative<'c>;
+}
+
+/// The trait of storable values whose generative form does not depend on the lifetime.
+///
+/// It is implemented for every such type, so plain payloads may be stored next to branded data without a marker:
+///
+/// ```
+/// # use genz::*;
+/// struct Config { retries: u32 }
+///
+/// impl Storable for Config {
+///   type Generative<'c> = Config;
+/// }
+///
+/// let mut config = Gen::from_value(Config { retries: 3 });
+/// config.value_mut().retries += 1;
+/// assert_eq!(4, config.value().retries);
+/// ```
+pub trait BrandFree: for <'c> Storable<Generative<'c> = Self> {}
+
+impl<Z> BrandFree for Z
+  where
+    Z: for <'c> Storable<Generative<'c> = Z>
+{}
+
 impl Storable for Region<'static> {
   type Generative<'c> = Region<'c>;
 }
   
-impl<T> Storable for UniqueType<'static, T> {
+impl<T: ?Sized> Storable for UniqueType<'static, T> {
   type Generative<'c> = UniqueType<'c, T>;
 }
 
+impl<Z: Storable> Storable for Pin<Box<Z>>
+  where
+    Pin<Box<Z>>: From<Pin<Box<Z::Generative<'static>>>>,
+    Pin<Box<Z>>: BorrowMut<Pin<Box<Z::Generative<'static>>>>,
+    Pin<Box<Z>>: Into<Pin<Box<Z::Generative<'static>>>>
+{
+  type Generative<'c> = Pin<Box<Z::Generative<'c>>>;
+}
+
+impl<Z: Storable> Storable for Vec<Z>
+  where
+    Vec<Z>: From<Vec<Z::Generative<'static>>>,
+    Vec<Z>: BorrowMut<Vec<Z::Generative<'static>>>,
+    Vec<Z>: Into<Vec<Z::Generative<'static>>>
+{
+  type Generative<'c> = Vec<Z::Generative<'c>>;
+}
+
 impl<T> Storable for Gen<T> {
   type Generative<'c> = Gen<T>;
-}
\ No newline at end of file
+}
+
+impl<T: ?Sized, Z: Storable> Storable for SelfRef<T, Z> {
+  type Generative<'c> = SelfRef<T, Z>;
+}
+
+impl CloneGenerative for Region<'static> {
+  #[inline]
+  fn clone_generative<'c>(value: &Self::Generative<'c>) -> Self::Generative<'c>
+  {
+    *value
+  }
+}
+
+impl GenerativeDefault for Region<'static> {
+  #[inline]
+  fn default_with<'c>(ty: UniqueType<'c, ()>) -> Self::Generative<'c>
+  {
+    ty.into()
+  }
+}
+
+impl<T: ?Sized> GenerativeDefault<T> for UniqueType<'static, T> {
+  #[inline]
+  fn default_with<'c>(ty: UniqueType<'c, T>) -> Self::Generative<'c>
+  {
+    ty
+  }
+}
+
+impl<Z: CloneGenerative> CloneGenerative for Gen<Z> {
+  #[inline]
+  fn clone_generative<'c>(value: &Self::Generative<'c>) -> Self::Generative<'c>
+  {
+    value.snapshot()
+  }
+}
+
+/// Implement `Storable` for boxed trait objects of a trait parameterized by the lifetime.
+///
+/// The stored form of `$trait` is `Box<dyn $trait<'static>>`, and its generative form is `Box<dyn $trait<'c> + 'c>`,
+/// so values borrowing nothing but the lifetime may be stored behind the trait:
+///
+/// ```
+/// # use genz::*;
+/// trait Shape<'c> {
+///   fn area(&self) -> u32;
+/// }
+///
+/// struct Square<'c>(Region<'c>, u32);
+///
+/// impl<'c> Shape<'c> for Square<'c> {
+///   fn area(&self) -> u32 { self.1 * self.1 }
+/// }
+///
+/// storable_dyn!(Shape);
+///
+/// let shape = Gen::<Box<dyn Shape>>::from_type::<()>(|ty| Box::new(Square(ty.into(), 3)));
+/// assert_eq!(9, shape.with_ref(|shape| shape.area()));
+/// ```
+///
+/// The trait must be local to the crate invoking the macro.
+#[macro_export]
+macro_rules! storable_dyn {
+  ($($trait:ident),+ $(,)?) => {
+    $(
+      impl $crate::Storable for ::std::boxed::Box<dyn $trait<'static> + 'static> {
+        type Generative<'c> = ::std::boxed::Box<dyn $trait<'c> + 'c>;
+      }
+    )+
+  };
+}
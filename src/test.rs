@@ -0,0 +1,222 @@
+//! A deterministic scheduler for testing structures built on token cells, which shrinks the schedules that fail.
+//!
+//! A test draws every decision from a `Schedule`, most often by handing the token of a region to one of several tasks
+//! at a time with `Schedule::interleave`. `explore` runs the test with many pseudo-random schedules derived from a
+//! seed, and shrinks the first failing one, which `replay` then reproduces exactly:
+//!
+//! ```
+//! # use genz::*;
+//! use genz::test::{explore, replay, Schedule};
+//!
+//! // increments in two steps, so that another task may run in between
+//! fn increment<'r, 'c>(counter: &'r TokenCell<'c, u32>) -> impl FnMut(&mut Token<'c>) -> bool + 'r
+//! {
+//!   let mut read = None;
+//!   move |token| match read.take() {
+//!     None => {
+//!       read = Some(*counter.borrow(token));
+//!       true
+//!     }
+//!     Some(value) => {
+//!       *counter.borrow_mut(token) = value + 1;
+//!       false
+//!     }
+//!   }
+//! }
+//!
+//! let test = |schedule: &mut Schedule| with_token(|mut token| {
+//!   let counter = TokenCell::new(0, token.region());
+//!   schedule.interleave(&mut token, &mut [&mut increment(&counter), &mut increment(&counter)]);
+//!   match *counter.borrow(&token) {
+//!     2 => Ok(()),
+//!     total => Err(format!("lost an update, the total is {total}"))
+//!   }
+//! });
+//!
+//! let failure = explore(7, 100, test).unwrap_err();
+//! assert_eq!("lost an update, the total is 1", failure.message());
+//! assert_eq!(Err(failure.message().to_string()), replay(failure.choices(), test));
+//! ```
+//!
+//! Tests may also panic, e.g. on a failed assertion, which fails the schedule like returning an error does. Panic
+//! messages are still printed by the panic hook while exploring.
+//!
+//! Schedules only order steps of a single thread, so the tasks need no synchronization and the runs are reproducible,
+//! but data races between threads are out of scope.
+
+use std::{any::Any, fmt, panic::{self, AssertUnwindSafe}};
+use crate::Token;
+
+/// The source of every decision of a test run.
+///
+/// Decisions are pseudo-random when exploring, and read back from recorded choices when replaying. Choices beyond the
+/// recorded ones are `0`.
+pub struct Schedule
+{
+  recorded: Vec<usize>,
+  state: Option<u64>,
+  taken: Vec<usize>
+}
+
+/// A failing schedule found by `explore`, after shrinking.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Failure
+{
+  choices: Vec<usize>,
+  message: String
+}
+
+/// Run `test` with `runs` schedules derived from `seed`, returning the first failure after shrinking it.
+///
+/// A failure is shrunk by removing and lowering its choices for as long as the test keeps failing, so the choices of
+/// the returned failure are as short and small as this search finds.
+pub fn explore(seed: u64, runs: usize, test: impl Fn(&mut Schedule) -> Result<(), String>) -> Result<(), Failure>
+{
+  let mut seeds = seed;
+  for _ in 0 .. runs {
+    let schedule = Schedule { recorded: Vec::new(), state: Some(next(&mut seeds)), taken: Vec::new() };
+    if let Err((choices, message)) = run(schedule, &test) {
+      return Err(shrink(choices, message, &test));
+    }
+  }
+  Ok(())
+}
+
+/// Run `test` once with the decisions `choices`, as returned by `Failure::choices`.
+pub fn replay(choices: &[usize], test: impl Fn(&mut Schedule) -> Result<(), String>) -> Result<(), String>
+{
+  run(Schedule::replaying(choices.to_vec()), &test).map_err(|(_, message)| message)
+}
+
+impl Schedule
+{
+  /// Returns a choice in `0 .. n`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `n` is `0`.
+  pub fn choose(&mut self, n: usize) -> usize
+  {
+    assert!(n > 0, "there must be something to choose");
+    let choice = match (self.recorded.get(self.taken.len()), &mut self.state) {
+      (Some(&choice), _) => choice.min(n - 1),
+      (None, Some(state)) => (next(state) % n as u64) as usize,
+      (None, None) => 0
+    };
+    self.taken.push(choice);
+    choice
+  }
+
+  /// Run `tasks` to completion, handing `token` to one chosen task per step.
+  ///
+  /// A task takes a step per call, and returns `false` once it has finished. Every task is called at least once.
+  pub fn interleave<'c>(&mut self, token: &mut Token<'c>, tasks: &mut [&mut dyn FnMut(&mut Token<'c>) -> bool])
+  {
+    let mut running: Vec<usize> = (0 .. tasks.len()).collect();
+    while !running.is_empty() {
+      let chosen = self.choose(running.len());
+      if !tasks[running[chosen]](token) {
+        running.remove(chosen);
+      }
+    }
+  }
+
+  /// The choices made so far.
+  #[inline]
+  pub fn choices(&self) -> &[usize]
+  {
+    &self.taken
+  }
+
+  // A schedule which reads back `recorded`.
+  #[inline]
+  fn replaying(recorded: Vec<usize>) -> Self
+  {
+    Schedule { recorded, state: None, taken: Vec::new() }
+  }
+}
+
+impl Failure
+{
+  /// The choices of the failing run, for `replay`.
+  #[inline]
+  pub fn choices(&self) -> &[usize]
+  {
+    &self.choices
+  }
+
+  /// The error returned by the failing run, or the message it panicked with.
+  #[inline]
+  pub fn message(&self) -> &str
+  {
+    &self.message
+  }
+}
+
+impl fmt::Display for Failure
+{
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+  {
+    write!(f, "{} with the choices {:?}", self.message, self.choices)
+  }
+}
+
+impl std::error::Error for Failure {}
+
+// Run `test`, returning the choices it made and its message if it fails.
+fn run(mut schedule: Schedule, test: &dyn Fn(&mut Schedule) -> Result<(), String>) -> Result<(), (Vec<usize>, String)>
+{
+  let message = match panic::catch_unwind(AssertUnwindSafe(|| test(&mut schedule))) {
+    Ok(Ok(())) => return Ok(()),
+    Ok(Err(message)) => message,
+    Err(payload) => panic_message(payload)
+  };
+  Err((schedule.taken, message))
+}
+
+// Replace `choices` by any shorter or smaller choices which still fail, until none is found.
+fn shrink(mut choices: Vec<usize>, mut message: String, test: &dyn Fn(&mut Schedule) -> Result<(), String>) -> Failure
+{
+  let mut index = 0;
+  while index < choices.len() {
+    let mut removed = choices.clone();
+    removed.remove(index);
+    let lowered = [0, choices[index] / 2, choices[index].saturating_sub(1)].map(|lower| {
+      let mut lowered = choices.clone();
+      lowered[index] = lower;
+      lowered
+    });
+    let smaller = std::iter::once(removed).chain(lowered).find_map(|candidate| match run(Schedule::replaying(candidate), test) {
+      // a run may take more choices than the candidate has, so only runs taking smaller choices are kept
+      Err((taken, failed)) if (taken.len(), &taken) < (choices.len(), &choices) => Some((taken, failed)),
+      _ => None
+    });
+    match smaller {
+      Some(smaller) => {
+        (choices, message) = smaller;
+        index = 0;
+      }
+      None => index += 1
+    }
+  }
+  Failure { choices, message }
+}
+
+// The message of a panic with `payload`.
+fn panic_message(payload: Box<dyn Any + Send>) -> String
+{
+  match payload.downcast::<String>() {
+    Ok(message) => *message,
+    Err(payload) => payload.downcast_ref::<&str>().map_or("a panic without a message", |message| message).to_string()
+  }
+}
+
+// The next value of a SplitMix64 generator.
+fn next(state: &mut u64) -> u64
+{
+  *state = state.wrapping_add(0x9e3779b97f4a7c15);
+  let z = *state;
+  let z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+  let z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+  z ^ (z >> 31)
+}
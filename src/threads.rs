@@ -0,0 +1,113 @@
+//! A thread pool whose jobs run in regions of their own.
+
+use std::{panic::{self, AssertUnwindSafe}, sync::{mpsc, Arc, Mutex}, thread::{self, JoinHandle}};
+use crate::{lifetime::STATIC_REGION, with_region, Gen, Region, Storable};
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// A fixed set of worker threads, each job of which receives a fresh invariant lifetime.
+///
+/// Results must be free of the brand, because they outlive the job. Stored values may be shared with jobs through an
+/// `Arc`, in which case each job opens the value with its own lifetime:
+///
+/// ```
+/// # use genz::*;
+/// # use std::sync::Arc;
+/// struct Words<'c>(Region<'c>, Vec<&'static str>);
+///
+/// impl Storable for Words<'static> {
+///   type Generative<'c> = Words<'c>;
+/// }
+///
+/// let words = Arc::new(Gen::<Words>::from_type::<()>(|ty| Words(ty.into(), vec!["a", "bc", "def"])));
+/// let pool = ThreadPool::new(2);
+///
+/// let jobs: Vec<_> = (0 .. 3).map(|i| pool.submit_shared(&words, move |_, words| words.1[i].len())).collect();
+/// let lengths: Vec<_> = jobs.into_iter().map(JobHandle::join).collect();
+/// assert_eq!(vec![1, 2, 3], lengths);
+///
+/// assert_eq!(7, pool.submit(|_| 7).join());
+/// ```
+///
+/// ```compile_fail
+/// # use genz::*;
+/// let pool = ThreadPool::new(1);
+/// pool.submit(|region| region).join(); // fails because the result contains the brand
+/// ```
+pub struct ThreadPool
+{
+  sender: Option<mpsc::Sender<Job>>,
+  workers: Vec<JoinHandle<()>>
+}
+
+/// The pending result of a job submitted to a `ThreadPool`.
+pub struct JobHandle<R>(mpsc::Receiver<R>);
+
+impl<R> JobHandle<R>
+{
+  /// Wait for the job to finish, returning its result.
+  ///
+  /// Panics if the job panicked.
+  #[inline]
+  pub fn join(self) -> R
+  {
+    self.0.recv().expect("the job panicked")
+  }
+}
+
+impl ThreadPool
+{
+  /// Start a pool of `threads` workers.
+  pub fn new(threads: usize) -> Self
+  {
+    assert!(threads > 0, "a pool needs at least one thread");
+    let (sender, receiver) = mpsc::channel::<Job>();
+    let receiver = Arc::new(Mutex::new(receiver));
+    let workers = (0 .. threads).map(|_| {
+      let receiver = Arc::clone(&receiver);
+      thread::spawn(move || loop {
+        let job = receiver.lock().unwrap_or_else(|err| err.into_inner()).recv();
+        match job {
+          Ok(job) => { let _ = panic::catch_unwind(AssertUnwindSafe(job)); }
+          Err(_) => break
+        }
+      })
+    }).collect();
+    ThreadPool { sender: Some(sender), workers }
+  }
+
+  /// Run `job` on a worker with a fresh invariant lifetime.
+  pub fn submit<R: Send + 'static>(&self, job: impl for <'c> FnOnce(Region<'c>) -> R + Send + 'static) -> JobHandle<R>
+  {
+    self.spawn(move || with_region(job))
+  }
+
+  /// Run `job` on a worker with a fresh invariant lifetime, and a reference to the `shared` value opened with it.
+  pub fn submit_shared<Z, R>(&self, shared: &Arc<Gen<Z>>, job: impl for <'c> FnOnce(Region<'c>, &Z::Generative<'c>) -> R + Send + 'static) -> JobHandle<R>
+    where
+      Z: Storable + Send + Sync + 'static,
+      R: Send + 'static
+  {
+    let shared = Arc::clone(shared);
+    self.spawn(move || job(STATIC_REGION, shared.0.borrow()))
+  }
+
+  fn spawn<R: Send + 'static>(&self, job: impl FnOnce() -> R + Send + 'static) -> JobHandle<R>
+  {
+    let (sender, receiver) = mpsc::channel();
+    let job: Job = Box::new(move || { let _ = sender.send(job()); });
+    self.sender.as_ref().expect("the sender lives as long as the pool").send(job).expect("workers outlive the pool");
+    JobHandle(receiver)
+  }
+}
+
+impl Drop for ThreadPool
+{
+  fn drop(&mut self)
+  {
+    drop(self.sender.take());
+    for worker in self.workers.drain(..) {
+      let _ = worker.join();
+    }
+  }
+}
@@ -0,0 +1,90 @@
+//! Values which have been checked by a validator within a region.
+
+use std::marker::PhantomData;
+use crate::Region;
+
+/// A check which values of type `T` may pass.
+pub trait Validator<T: ?Sized>
+{
+  /// The reason a value fails the check.
+  type Error;
+
+  /// Returns an error if `value` fails the check.
+  fn validate(value: &T) -> Result<(), Self::Error>;
+}
+
+/// A value of type `T` which passed the validator `V` in the region `'c`.
+///
+/// The proof of validation cannot be forged, because a `Validated` can only be created by running `V`, and it cannot be
+/// carried into another region, so functions can require inputs which were validated in their own region:
+///
+/// ```
+/// # use genz::*;
+/// struct NonEmpty;
+///
+/// impl Validator<String> for NonEmpty {
+///   type Error = &'static str;
+///
+///   fn validate(value: &String) -> Result<(), &'static str>
+///   {
+///     if value.is_empty() { Err("empty") } else { Ok(()) }
+///   }
+/// }
+///
+/// fn greet<'c>(_: Region<'c>, name: &Validated<'c, String, NonEmpty>) -> String
+/// {
+///   format!("hello, {}", name.get())
+/// }
+///
+/// with_region(|region| {
+///   let name = Validated::<_, NonEmpty>::new(region, "genz".to_owned()).unwrap();
+///   assert_eq!("hello, genz", greet(region, &name));
+///
+///   assert_eq!(Err("empty"), Validated::<_, NonEmpty>::new(region, String::new()).map(|_| ()));
+/// });
+/// ```
+///
+/// ```compile_fail
+/// # use genz::*;
+/// # struct NonEmpty;
+/// # impl Validator<String> for NonEmpty {
+/// #   type Error = ();
+/// #   fn validate(value: &String) -> Result<(), ()> { Ok(()) }
+/// # }
+/// # fn greet<'c>(_: Region<'c>, name: &Validated<'c, String, NonEmpty>) {}
+/// with_region(|a| {
+///   let name = Validated::<_, NonEmpty>::new(a, "genz".to_owned()).unwrap();
+///   with_region(|b| greet(b, &name)); // fails because `name` was validated in a different region
+/// });
+/// ```
+pub struct Validated<'c, T, V>
+{
+  value: T,
+  _region: PhantomData<(Region<'c>, fn() -> V)>
+}
+
+impl<'c, T, V> Validated<'c, T, V>
+{
+  /// Run `V` on `value`, returning the validated value, or the error if the check fails.
+  #[inline]
+  pub fn new(_: Region<'c>, value: T) -> Result<Self, V::Error>
+    where
+      V: Validator<T>
+  {
+    V::validate(&value).map(|()| Validated { value, _region: PhantomData })
+  }
+
+  /// Returns a reference to the value.
+  #[inline]
+  pub fn get(&self) -> &T
+  {
+    &self.value
+  }
+
+  /// Consume the proof of validation, returning the value.
+  #[inline]
+  pub fn into_inner(self) -> T
+  {
+    self.value
+  }
+}
@@ -0,0 +1,396 @@
+//! Vectors with branded indices, and cursors which edit them while keeping track of which indices stay valid.
+
+use std::marker::PhantomData;
+use crate::{nonmax::{NonMaxU32, MAX_LEN}, with_region, Region};
+
+/// An in-bounds index of the `BrandedVec<'c, _>`.
+///
+/// The index is stored as a `u32` with a niche, so an `Option<BrandedIndex<'c>>` is no larger than a `u32`:
+///
+/// ```
+/// # use genz::*;
+/// assert_eq!(4, std::mem::size_of::<Option<BrandedIndex>>());
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct BrandedIndex<'c>(NonMaxU32, Region<'c>);
+
+impl BrandedIndex<'_>
+{
+  /// The position of the element.
+  #[inline]
+  pub fn index(self) -> usize
+  {
+    self.0.get()
+  }
+
+  /// The position of the element as a `u32`, e.g. for serialization. It is turned back into an index by
+  /// `BrandedVec::index`.
+  #[inline]
+  pub fn raw(self) -> u32
+  {
+    self.0.raw()
+  }
+}
+
+/// A vector whose indices are branded with lifetime `'c`.
+///
+/// The vector may grow, but only shrinks through a `Cursor`. Each index is checked once when it is created, after which
+/// accessing its element needs no bounds check:
+///
+/// ```
+/// # use genz::*;
+/// with_branded_vec(vec![1, 2], |mut values| {
+///   let first = values.index(0).unwrap();
+///   let third = values.push(3);
+///   *values.get_mut(first) += 10;
+///
+///   assert_eq!((11, 3), (*values.get(first), *values.get(third)));
+///   assert!(values.index(3).is_none());
+/// });
+/// ```
+///
+/// Indices of one vector cannot be used with another:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// with_branded_vec(vec![1], |a| {
+///   with_branded_vec(vec![2], |b| {
+///     b.get(a.index(0).unwrap()); // fails because `a` and `b` have different lifetimes
+///   });
+/// });
+/// ```
+pub struct BrandedVec<'c, T>
+{
+  vec: Vec<T>,
+  region: Region<'c>
+}
+
+/// The state of a `Cursor` which has not changed its vector, so every index of the vector is still valid.
+pub struct Unedited;
+
+/// The state of a `Cursor` which has inserted or removed elements, so each index of the vector must be remapped.
+pub struct Edited;
+
+/// A cursor which inserts and removes elements at its position in the `BrandedVec<'c, T>`, in the state `S`.
+///
+/// The cursor takes the vector, so indices branded with `'c` can only be used through the cursor while it edits. An
+/// `Unedited` cursor looks them up infallibly. Once an element was inserted or removed, the cursor is `Edited`, and each
+/// index is remapped to the position of its element, or `None` if the element was removed. Finishing the edits brands
+/// the vector anew, with a `Remap` which carries the outstanding indices over:
+///
+/// ```
+/// # use genz::*;
+/// with_branded_vec(vec!["a", "b", "c", "d"], |names| {
+///   let [a, b, c, d] = [0, 1, 2, 3].map(|index| names.index(index).unwrap());
+///
+///   let cursor = names.cursor(b);
+///   assert_eq!("d", *cursor.get(d));
+///
+///   let mut cursor = cursor.insert_before("ab");
+///   assert!(cursor.move_next());
+///   assert_eq!(Some("c"), cursor.remove_at());
+///
+///   // the indices before the edits are unchanged, and the ones after them are shifted
+///   assert_eq!((Some(&"a"), Some(&"d"), None), (cursor.get(a), cursor.get(d), cursor.get(c)));
+///
+///   cursor.finish(|names, remap| {
+///     let d = remap.get(d).unwrap();
+///     assert_eq!(("d", 3), (*names.get(d), d.index()));
+///     assert_eq!(Some(2), remap.get(b).map(BrandedIndex::index));
+///     assert_eq!(vec!["a", "ab", "b", "d"], names.into_vec());
+///   });
+/// });
+/// ```
+///
+/// Indices of the edited vector cannot be used with the vector produced by the edits:
+///
+/// ```compile_fail
+/// # use genz::*;
+/// with_branded_vec(vec![1, 2], |values| {
+///   let first = values.index(0).unwrap();
+///   let (_, cursor) = values.cursor(first).remove_at();
+///   cursor.finish(|values, _| {
+///     values.get(first); // fails because the edits brand the vector with a new lifetime
+///   });
+/// });
+/// ```
+pub struct Cursor<'c, T, S>
+{
+  vec: Vec<T>,
+  position: usize,
+  // the index of the element at `position` before the edits, which are recorded in the same coordinates
+  original: usize,
+  edits: Edits,
+  region: Region<'c>,
+  _state: PhantomData<S>
+}
+
+/// The positions of the elements of the `BrandedVec<'c, _>` in the `BrandedVec<'d, _>` produced by editing it.
+pub struct Remap<'c, 'd>
+{
+  edits: Edits,
+  _from: Region<'c>,
+  to: Region<'d>
+}
+
+// Edits in the coordinates of the original vector, which are sorted because a cursor only moves forward.
+#[derive(Default)]
+struct Edits
+{
+  // an entry per element inserted before the original element at that index
+  inserted: Vec<usize>,
+  removed: Vec<usize>
+}
+
+impl Edits
+{
+  // Returns the new index of the original element at `index`, or `None` if it was removed.
+  fn map(&self, index: usize) -> Option<usize>
+  {
+    self.removed.binary_search(&index).is_err().then(|| {
+      index + self.inserted.partition_point(|&at| at <= index) - self.removed.partition_point(|&at| at < index)
+    })
+  }
+}
+
+/// Invoke `f` with `vec` as a vector branded with an invariant lifetime.
+///
+/// # Panics
+///
+/// Panics if `vec` holds more than `u32::MAX` elements.
+#[inline]
+pub fn with_branded_vec<T, Z>(vec: Vec<T>, f: impl for <'c> FnOnce(BrandedVec<'c, T>) -> Z) -> Z
+{
+  assert!(vec.len() <= MAX_LEN, "a branded vector holds at most `u32::MAX` elements");
+  with_region(|region| f(BrandedVec { vec, region }))
+}
+
+impl<'c, T> BrandedVec<'c, T>
+{
+  /// Returns the index `index`, or `None` if it is out of bounds.
+  #[inline]
+  pub fn index(&self, index: usize) -> Option<BrandedIndex<'c>>
+  {
+    // SAFETY: `index < len <= MAX_LEN`.
+    (index < self.vec.len()).then(|| BrandedIndex(unsafe { NonMaxU32::new_unchecked(index) }, self.region))
+  }
+
+  /// Returns an iterator over the indices of every element.
+  #[inline]
+  pub fn indices(&self) -> impl Iterator<Item = BrandedIndex<'c>>
+  {
+    let region = self.region;
+    // SAFETY: `index < len <= MAX_LEN`.
+    (0 .. self.vec.len()).map(move |index| BrandedIndex(unsafe { NonMaxU32::new_unchecked(index) }, region))
+  }
+
+  /// Append `value`, returning its index.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the vector already holds `u32::MAX` elements.
+  #[inline]
+  pub fn push(&mut self, value: T) -> BrandedIndex<'c>
+  {
+    let index = NonMaxU32::new(self.vec.len()).expect("a branded vector holds at most `u32::MAX` elements");
+    self.vec.push(value);
+    BrandedIndex(index, self.region)
+  }
+
+  /// Returns a reference to the element at `index`.
+  #[inline]
+  pub fn get(&self, index: BrandedIndex<'c>) -> &T
+  {
+    // SAFETY: `index` was checked against this vector, which does not shrink while it is branded with `'c`.
+    unsafe { self.vec.get_unchecked(index.index()) }
+  }
+
+  /// Returns a mutable reference to the element at `index`.
+  #[inline]
+  pub fn get_mut(&mut self, index: BrandedIndex<'c>) -> &mut T
+  {
+    // SAFETY: `index` was checked against this vector, which does not shrink while it is branded with `'c`.
+    unsafe { self.vec.get_unchecked_mut(index.index()) }
+  }
+
+  /// The number of elements.
+  #[inline]
+  pub fn len(&self) -> usize
+  {
+    self.vec.len()
+  }
+
+  /// Returns `true` if the vector has no elements.
+  #[inline]
+  pub fn is_empty(&self) -> bool
+  {
+    self.vec.is_empty()
+  }
+
+  /// Returns a cursor at the element of `index`.
+  #[inline]
+  pub fn cursor(self, index: BrandedIndex<'c>) -> Cursor<'c, T, Unedited>
+  {
+    self.cursor_at(index.index())
+  }
+
+  /// Returns a cursor past the last element, where inserted elements are appended.
+  #[inline]
+  pub fn cursor_end(self) -> Cursor<'c, T, Unedited>
+  {
+    let len = self.vec.len();
+    self.cursor_at(len)
+  }
+
+  /// Returns the elements, giving up the brand.
+  #[inline]
+  pub fn into_vec(self) -> Vec<T>
+  {
+    self.vec
+  }
+
+  #[inline]
+  fn cursor_at(self, position: usize) -> Cursor<'c, T, Unedited>
+  {
+    Cursor { vec: self.vec, position, original: position, edits: Edits::default(), region: self.region, _state: PhantomData }
+  }
+}
+
+impl<'c, T, S> Cursor<'c, T, S>
+{
+  /// The current position of the cursor, which is the length of the vector if it is past the last element.
+  #[inline]
+  pub fn position(&self) -> usize
+  {
+    self.position
+  }
+
+  /// Returns a reference to the element at the cursor, or `None` if it is past the last element.
+  #[inline]
+  pub fn current(&self) -> Option<&T>
+  {
+    self.vec.get(self.position)
+  }
+
+  /// Returns a mutable reference to the element at the cursor, or `None` if it is past the last element.
+  #[inline]
+  pub fn current_mut(&mut self) -> Option<&mut T>
+  {
+    self.vec.get_mut(self.position)
+  }
+
+  /// Move the cursor to the next element, or return `false` if it is already past the last element.
+  #[inline]
+  pub fn move_next(&mut self) -> bool
+  {
+    let moved = self.position < self.vec.len();
+    if moved {
+      self.position += 1;
+      self.original += 1;
+    }
+    moved
+  }
+
+  #[inline]
+  fn edited(self) -> Cursor<'c, T, Edited>
+  {
+    let Cursor { vec, position, original, edits, region, .. } = self;
+    Cursor { vec, position, original, edits, region, _state: PhantomData }
+  }
+}
+
+impl<'c, T> Cursor<'c, T, Unedited>
+{
+  /// Returns a reference to the element at `index`.
+  #[inline]
+  pub fn get(&self, index: BrandedIndex<'c>) -> &T
+  {
+    // SAFETY: the vector has not changed since `index` was checked against it.
+    unsafe { self.vec.get_unchecked(index.index()) }
+  }
+
+  /// Insert `value` before the element at the cursor, which stays at that element.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the vector already holds `u32::MAX` elements.
+  #[inline]
+  pub fn insert_before(self, value: T) -> Cursor<'c, T, Edited>
+  {
+    let mut cursor = self.edited();
+    cursor.insert_before(value);
+    cursor
+  }
+
+  /// Remove and return the element at the cursor, which moves to the next element, or return `None` if the cursor is
+  /// past the last element.
+  #[inline]
+  pub fn remove_at(self) -> (Option<T>, Cursor<'c, T, Edited>)
+  {
+    let mut cursor = self.edited();
+    (cursor.remove_at(), cursor)
+  }
+
+  /// Give the vector back, which is still branded with `'c` because it has not changed.
+  #[inline]
+  pub fn finish(self) -> BrandedVec<'c, T>
+  {
+    BrandedVec { vec: self.vec, region: self.region }
+  }
+}
+
+impl<'c, T> Cursor<'c, T, Edited>
+{
+  /// Returns a reference to the element at `index`, or `None` if it was removed.
+  #[inline]
+  pub fn get(&self, index: BrandedIndex<'c>) -> Option<&T>
+  {
+    // SAFETY: the elements which were not removed are at their remapped positions.
+    self.edits.map(index.index()).map(|index| unsafe { self.vec.get_unchecked(index) })
+  }
+
+  /// Insert `value` before the element at the cursor, which stays at that element.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the vector already holds `u32::MAX` elements.
+  #[inline]
+  pub fn insert_before(&mut self, value: T)
+  {
+    assert!(self.vec.len() < MAX_LEN, "a branded vector holds at most `u32::MAX` elements");
+    self.vec.insert(self.position, value);
+    self.edits.inserted.push(self.original);
+    self.position += 1;
+  }
+
+  /// Remove and return the element at the cursor, which moves to the next element, or return `None` if the cursor is
+  /// past the last element.
+  #[inline]
+  pub fn remove_at(&mut self) -> Option<T>
+  {
+    (self.position < self.vec.len()).then(|| {
+      self.edits.removed.push(self.original);
+      self.original += 1;
+      self.vec.remove(self.position)
+    })
+  }
+
+  /// Invoke `f` with the edited vector branded with a new invariant lifetime, and the remapping of its indices.
+  #[inline]
+  pub fn finish<Z>(self, f: impl for <'d> FnOnce(BrandedVec<'d, T>, Remap<'c, 'd>) -> Z) -> Z
+  {
+    let Cursor { vec, edits, region: from, .. } = self;
+    with_region(|to| f(BrandedVec { vec, region: to }, Remap { edits, _from: from, to }))
+  }
+}
+
+impl<'c, 'd> Remap<'c, 'd>
+{
+  /// Returns the index of the element at `index` in the edited vector, or `None` if it was removed.
+  #[inline]
+  pub fn get(&self, index: BrandedIndex<'c>) -> Option<BrandedIndex<'d>>
+  {
+    // SAFETY: the remapped index is less than the length of the edited vector, which is at most `MAX_LEN`.
+    self.edits.map(index.index()).map(|index| BrandedIndex(unsafe { NonMaxU32::new_unchecked(index) }, self.to))
+  }
+}